@@ -1,74 +1,236 @@
-use std::cmp::PartialEq;
+use advent_of_code::parse::{ParseError, parse_capture, parse_lines};
+use advent_of_code::record::{Picture, Scene, Shape};
+use advent_of_code::viz::{self, Colour, Frame};
+use std::f64::consts::TAU;
+use std::fmt;
 
 advent_of_code::solution!(1);
 
-const MAX_NUM: u64 = 100;
+const DIAL_SIZE: u64 = 100;
+const DIAL_START: u64 = 50;
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
 enum Direction {
     Left,
     Right,
 }
 
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+struct Rotation {
+    direction: Direction,
+    distance: u64,
+}
+
+/// What happened during one rotation of the dial.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+struct Step {
+    rotation: Rotation,
+    /// Position after the rotation.
+    position: u64,
+    /// Number of clicks that moved the dial across the seam between its last number and `0`.
+    wraps: u64,
+    /// Number of clicks that left the dial pointing at `0`, including the last one.
+    zero_hits: u64,
+}
+
+/// A dial numbered `0..size` that clicks one number at a time.
+struct Dial {
+    size: u64,
+    position: u64,
+}
+
 pub fn part_one(input: &str) -> Option<u64> {
-    let mut position = 50;
-    let mut zero_count = 0;
-    for (direction, number) in parse(input) {
-        position = match direction {
-            Direction::Left => position - number as i64,
-            Direction::Right => position + number as i64,
-        }
-        .rem_euclid(MAX_NUM as i64);
+    let zero_count = trace(parse(input))
+        .filter(|step| step.position == 0)
+        .count();
+    Some(zero_count as u64)
+}
 
-        if position == 0 {
-            zero_count += 1;
-        }
+pub fn part_two(input: &str) -> Option<u64> {
+    let mut zero_hits = 0;
+    for step in trace(parse(input)) {
+        zero_hits += step.zero_hits;
+        viz::frame(|| {
+            Frame::text(render_dial(&step))
+                .caption(step)
+                .caption(format_args!("zero hits so far: {zero_hits}"))
+                .with_picture(Picture::Scene(draw_dial(&step)))
+        });
     }
-    Some(zero_count)
+    Some(zero_hits)
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let mut position = 50;
-    let mut zero_count = 0;
-    for (direction, number) in parse(input) {
-        let full_turns = if position == 0 {
-            number
-        } else {
-            match direction {
-                Direction::Left => number + MAX_NUM - position as u64,
-                Direction::Right => number + position as u64,
-            }
-        } / MAX_NUM;
+/// Runs the rotations on the puzzle's dial, one step per rotation.
+fn trace(rotations: impl Iterator<Item = Rotation>) -> impl Iterator<Item = Step> {
+    let mut dial = Dial::new(DIAL_SIZE, DIAL_START);
+    rotations.map(move |rotation| dial.rotate(rotation))
+}
 
-        position = match direction {
-            Direction::Left => position - number as i64,
-            Direction::Right => position + number as i64,
+/// The dial's scale as a strip, with zero at the left edge and the pointer highlighted.
+fn render_dial(step: &Step) -> String {
+    (0..DIAL_SIZE)
+        .map(|number| match number {
+            _ if number == step.position => viz::paint('▲', Colour::Yellow),
+            0 => viz::paint('0', Colour::Red),
+            _ if number % 10 == 0 => '|'.to_string(),
+            _ => '·'.to_string(),
+        })
+        .collect()
+}
+
+/// The dial face seen from the front, with zero at the top and the pointer in yellow.
+fn draw_dial(step: &Step) -> Scene {
+    let point = |number: u64, radius: f64| {
+        let angle = TAU * number as f64 / DIAL_SIZE as f64;
+        (radius * angle.sin(), -radius * angle.cos())
+    };
+
+    let mut scene = Scene::new((-1.2, -1.2), (1.2, 1.2));
+    for number in 0..DIAL_SIZE {
+        let colour = match number {
+            0 => Colour::Red,
+            _ if number % 10 == 0 => Colour::White,
+            _ => Colour::Grey,
+        };
+        scene.push(Shape::Dot {
+            centre: point(number, 1.0),
+            colour,
+        });
+    }
+    scene.push(Shape::Line {
+        from: (0.0, 0.0),
+        to: point(step.position, 0.9),
+        colour: Colour::Yellow,
+    });
+    scene
+}
+
+impl Dial {
+    fn new(size: u64, start: u64) -> Self {
+        assert!(
+            start < size,
+            "start {start} is not on a dial of size {size}"
+        );
+        Self {
+            size,
+            position: start,
         }
-        .rem_euclid(MAX_NUM as i64);
+    }
 
-        zero_count += full_turns;
+    fn rotate(&mut self, rotation: Rotation) -> Step {
+        let Self { size, position } = *self;
+        let distance = rotation.distance;
+
+        let step = match rotation.direction {
+            Direction::Right => {
+                // every multiple of `size` in `position + 1..=position + distance` is a hit, and
+                // going right each hit is also a wrap.
+                let zero_hits = (position + distance) / size;
+                Step {
+                    rotation,
+                    position: (position + distance) % size,
+                    wraps: zero_hits,
+                    zero_hits,
+                }
+            }
+            Direction::Left => {
+                // going left from `position` hits zero exactly as often as going right from its
+                // mirror image does.
+                let mirrored = (size - position) % size;
+                let zero_hits = (mirrored + distance) / size;
+                let end = (size - (mirrored + distance) % size) % size;
+                // going left, the wrap happens on the click *leaving* zero: count the start
+                // instead of the end.
+                let wraps = if distance == 0 {
+                    0
+                } else {
+                    zero_hits + (position == 0) as u64 - (end == 0) as u64
+                };
+                Step {
+                    rotation,
+                    position: end,
+                    wraps,
+                    zero_hits,
+                }
+            }
+        };
+
+        self.position = step.position;
+        step
     }
-    Some(zero_count)
 }
 
-fn parse(input: &str) -> impl Iterator<Item = (Direction, u64)> + '_ {
-    input.lines().map(|line| {
-        let mut chars = line.bytes();
-        let direction = if chars.next().unwrap() == b'L' {
-            Direction::Left
-        } else {
-            Direction::Right
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.direction {
+            Direction::Left => 'L',
+            Direction::Right => 'R',
         };
+        write!(f, "{direction}{}", self.distance)
+    }
+}
 
-        let number = chars.fold(0u64, |acc, b| acc * 10 + (b - b'0') as u64);
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {} (wraps: {}, zero hits: {})",
+            self.rotation, self.position, self.wraps, self.zero_hits
+        )
+    }
+}
 
-        (direction, number)
+fn parse(input: &str) -> impl Iterator<Item = Rotation> {
+    parse_lines(input, parse_rotation)
+        .unwrap_or_else(|err| panic!("invalid rotation: {err}"))
+        .into_iter()
+}
+
+fn parse_rotation(line: &str) -> Result<Rotation, ParseError> {
+    let (direction, distance) = match line.split_at_checked(1) {
+        Some(("L", distance)) => (Direction::Left, distance),
+        Some(("R", distance)) => (Direction::Right, distance),
+        _ => return Err(ParseError::new(1, "expected `L` or `R`")),
+    };
+
+    Ok(Rotation {
+        direction,
+        distance: parse_capture((1, distance))?,
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::generate::Rng;
+
+    /// Turns the dial one click at a time.
+    fn rotate_by_clicks(dial: &mut Dial, rotation: Rotation) -> Step {
+        let (mut wraps, mut zero_hits) = (0, 0);
+        for _ in 0..rotation.distance {
+            dial.position = match rotation.direction {
+                Direction::Right if dial.position == dial.size - 1 => {
+                    wraps += 1;
+                    0
+                }
+                Direction::Right => dial.position + 1,
+                Direction::Left if dial.position == 0 => {
+                    wraps += 1;
+                    dial.size - 1
+                }
+                Direction::Left => dial.position - 1,
+            };
+            if dial.position == 0 {
+                zero_hits += 1;
+            }
+        }
+        Step {
+            rotation,
+            position: dial.position,
+            wraps,
+            zero_hits,
+        }
+    }
 
     #[test]
     fn test_part_one() {
@@ -81,4 +243,56 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(6));
     }
+
+    #[test]
+    fn test_trace() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let steps: Vec<String> = trace(parse(&input)).map(|step| step.to_string()).collect();
+        assert_eq!(steps[0], "L68 -> 82 (wraps: 1, zero hits: 1)");
+        assert_eq!(steps[1], "L30 -> 52 (wraps: 0, zero hits: 0)");
+        assert_eq!(steps[2], "R48 -> 0 (wraps: 1, zero hits: 1)");
+        assert_eq!(steps[3], "L5 -> 95 (wraps: 1, zero hits: 0)");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_lines("R48\nL5x\n", parse_rotation).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+        assert!(err.message.contains("`5x`"));
+
+        let err = parse_lines("R48\n\nX5\n", parse_rotation).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn test_closed_form_matches_clicks() {
+        let mut rng = Rng::new(42);
+
+        for _ in 0..500 {
+            let size = rng.between(1, 12);
+            let start = rng.below(size);
+            let mut dial = Dial::new(size, start);
+            let mut oracle = Dial::new(size, start);
+
+            for _ in 0..20 {
+                let direction = if rng.chance(0.5) {
+                    Direction::Left
+                } else {
+                    Direction::Right
+                };
+                // up to several full turns, and exact multiples of the size.
+                let distance = match rng.below(4) {
+                    0 => size * rng.below(4),
+                    _ => rng.below(5 * size),
+                };
+                let rotation = Rotation {
+                    direction,
+                    distance,
+                };
+
+                let expected = rotate_by_clicks(&mut oracle, rotation);
+                assert_eq!(dial.rotate(rotation), expected, "size {size}");
+            }
+        }
+    }
 }
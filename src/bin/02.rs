@@ -1,89 +1,119 @@
+use advent_of_code::num::{
+    NON_ONE_DIVISORS, digit_len, is_repeated, mobius, pow10, repeat_multiplier,
+};
 use rayon::prelude::*;
 use std::ops::RangeInclusive;
 
 advent_of_code::solution!(2);
+advent_of_code::variants! {
+    part_one: [part_one_scan],
+    part_two: [part_two_scan],
+}
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let sum = par_parse(input)
-        .map(|id_range| id_range.into_par_iter().filter(is_invalid).sum::<u64>())
-        .sum();
-    Some(sum)
+    let sum = parse(input)
+        .map(|id_range| {
+            digit_lengths(&id_range)
+                .filter(|len| len % 2 == 0)
+                .map(|len| sum_repeated(&id_range, len, 2))
+                .sum::<u128>()
+        })
+        .sum::<u128>();
+    u64::try_from(sum).ok()
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let sum = par_parse(input)
+    let sum = parse(input)
         .map(|id_range| {
-            id_range
-                .into_par_iter()
-                .filter(|id| {
-                    let num_digits = id.ilog10() as u64 + 1;
-                    for &factor in NON_ONE_FACTORS[num_digits as usize - 1] {
-                        if is_invalid_n_repeats(id, num_digits, factor) {
-                            return true;
-                        }
-                    }
-                    false
-                })
-                .sum::<u64>()
+            digit_lengths(&id_range)
+                .map(|len| sum_any_repeated(&id_range, len))
+                .sum::<i128>()
         })
-        .sum();
-    Some(sum)
+        .sum::<i128>();
+    u64::try_from(sum).ok()
 }
 
-// we can pre-compute factors
-const NON_ONE_FACTORS: &[&[u64]] = &[
-    &[],                // 1
-    &[2],               // 2
-    &[3],               // 3
-    &[2, 4],            // 4
-    &[5],               // 5
-    &[2, 3, 6],         // 6
-    &[7],               // 7
-    &[2, 4, 8],         // 8
-    &[3, 9],            // 9
-    &[2, 5, 10],        // 10
-    &[11],              // 11
-    &[2, 3, 4, 6, 12],  // 12
-    &[13],              // 13
-    &[2, 7, 14],        // 14
-    &[3, 5, 15],        // 15
-    &[2, 4, 8, 16],     // 16
-    &[17],              // 17
-    &[2, 3, 6, 9, 18],  // 18
-    &[19],              // 19
-    &[2, 4, 5, 10, 20], // 20
-];
-
-fn is_invalid(id: &u64) -> bool {
-    let num_digits = id.ilog10() as u64 + 1;
-    let half = num_digits / 2;
-    let half_10 = 10_u64.pow(half as u32);
-    let first_half = id / half_10;
-    let second_half = id % half_10;
-    first_half == second_half
+/// [`part_one`] by checking every ID.
+pub fn part_one_scan(input: &str) -> Option<u64> {
+    Some(
+        parse(input)
+            .map(|id_range| sum_by_scan(id_range, false))
+            .sum(),
+    )
 }
 
-fn is_invalid_n_repeats(id: &u64, num_digits: u64, n: u64) -> bool {
-    let mut id = *id;
-    let part = num_digits / n;
-    let part_10 = 10_u64.pow(part as u32);
-
-    let mut prev_remainder = id % part_10;
-    id /= part_10;
-    let mut remainder;
-    for _ in 1..n {
-        remainder = id % part_10;
-        if remainder != prev_remainder {
-            return false;
-        }
-        id /= part_10;
-        prev_remainder = remainder;
+/// [`part_two`] by checking every ID.
+pub fn part_two_scan(input: &str) -> Option<u64> {
+    Some(
+        parse(input)
+            .map(|id_range| sum_by_scan(id_range, true))
+            .sum(),
+    )
+}
+
+/// Walks every ID in the range.
+fn sum_by_scan(id_range: RangeInclusive<u64>, any_repetition: bool) -> u64 {
+    id_range
+        .into_par_iter()
+        .filter(|&id| {
+            if !any_repetition {
+                return is_repeated(id, 2);
+            }
+            let num_digits = digit_len(id) as usize;
+            NON_ONE_DIVISORS[num_digits]
+                .as_slice()
+                .iter()
+                .any(|&factor| is_repeated(id, factor as u32))
+        })
+        .sum()
+}
+
+fn digit_lengths(id_range: &RangeInclusive<u64>) -> RangeInclusive<u32> {
+    digit_len(*id_range.start())..=digit_len(*id_range.end())
+}
+
+/// Sum of the `len` digit IDs in `id_range` that are a block repeated at least twice.
+///
+/// An ID made of a `len / t` digit block repeated `t` times is also a repetition for every
+/// multiple of `t` that divides `len`, so summing over all `t` would count it several times.
+/// Inclusion-exclusion over the repetition counts fixes that: the sets for `t` and `u` intersect
+/// in the set for `lcm(t, u)`, which works out to weighting each `t` by `-μ(t)`.
+fn sum_any_repeated(id_range: &RangeInclusive<u64>, len: u32) -> i128 {
+    NON_ONE_DIVISORS[len as usize]
+        .as_slice()
+        .iter()
+        .map(|&times| {
+            let weight = -mobius(times) as i128;
+            if weight == 0 {
+                return 0;
+            }
+            weight * sum_repeated(id_range, len, times as u32) as i128
+        })
+        .sum()
+}
+
+/// Sum of the `len` digit IDs in `id_range` that consist of one block repeated `times` times.
+///
+/// Those IDs are exactly `block * multiplier` for a `len / times` digit block, so the blocks in
+/// range form a contiguous run and their sum is an arithmetic series.
+fn sum_repeated(id_range: &RangeInclusive<u64>, len: u32, times: u32) -> u128 {
+    let block_len = len / times;
+    let multiplier: u128 = repeat_multiplier(block_len, times);
+
+    let low = (*id_range.start() as u128).max(pow10(len - 1));
+    let high = (*id_range.end() as u128).min(pow10::<u128>(len) - 1);
+    let first_block = low.div_ceil(multiplier).max(pow10(block_len - 1));
+    let last_block = (high / multiplier).min(pow10::<u128>(block_len) - 1);
+    if first_block > last_block {
+        return 0;
     }
-    true
+
+    let count = last_block - first_block + 1;
+    multiplier * (first_block + last_block) * count / 2
 }
 
-fn par_parse(input: &str) -> impl ParallelIterator<Item = RangeInclusive<u64>> + '_ {
-    input.trim_end().par_split(',').map(|line| {
+fn parse(input: &str) -> impl Iterator<Item = RangeInclusive<u64>> + '_ {
+    input.trim_end().split(',').map(|line| {
         let dash_index = line.find('-').unwrap();
         let first = line[0..dash_index].parse::<u64>().unwrap();
         let second = line[dash_index + 1..].parse::<u64>().unwrap();
@@ -94,6 +124,7 @@ fn par_parse(input: &str) -> impl ParallelIterator<Item = RangeInclusive<u64>> +
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::generate::Rng;
 
     #[test]
     fn test_part_one() {
@@ -106,4 +137,37 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(4174379265));
     }
+
+    #[test]
+    fn test_closed_form_matches_scan() {
+        let mut rng = Rng::new(37);
+
+        for _ in 0..300 {
+            let digits = rng.between(1, 12) as u32;
+            let start = rng.below(pow10::<u64>(digits));
+            let end = start + rng.below(20_000);
+            let input = format!("{start}-{end}");
+
+            assert_eq!(
+                part_one(&input),
+                Some(sum_by_scan(start..=end, false)),
+                "{input}"
+            );
+            assert_eq!(
+                part_two(&input),
+                Some(sum_by_scan(start..=end, true)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_ranges_near_the_top() {
+        // crosses from 19 to 20 digits, where the digit-length bounds no longer fit in a u64.
+        let (start, end) = (9_999_999_999_999_990_000, 10_000_000_000_000_010_000);
+        let input = format!("{start}-{end}");
+        assert_eq!(part_one(&input), Some(0));
+        assert_eq!(part_two(&input), Some(9_999_999_999_999_999_999));
+        assert_eq!(part_two(&input), Some(sum_by_scan(start..=end, true)));
+    }
 }
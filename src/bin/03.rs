@@ -1,56 +1,52 @@
-advent_of_code::solution!(3);
+advent_of_code::solution!(3, lines: [part_one_line, part_two_line]);
 
 pub fn part_one(input: &str) -> Option<u64> {
-    Some(parse(input).map(find_max_joltage).sum())
+    total_joltage(input, 2)
 }
 
-fn find_max_joltage(s: &str) -> u64 {
-    let bytes = s.as_bytes();
-    let mut tens = 0;
-    let mut tens_index = 0;
-
-    for (i, b) in bytes[..bytes.len() - 1].iter().enumerate() {
-        let num = b - b'0';
-        if num > tens {
-            tens = num;
-            tens_index = i;
-        }
-    }
+pub fn part_two(input: &str) -> Option<u64> {
+    total_joltage(input, 12)
+}
 
-    let mut ones = 0;
-    for b in bytes[tens_index + 1..].iter() {
-        let num = b - b'0';
-        if num > ones {
-            ones = num;
-        }
-    }
+fn part_one_line(bank: &str) -> Option<u128> {
+    max_subsequence_number(bank.as_bytes(), 2)
+}
 
-    (tens * 10 + ones) as u64
+fn part_two_line(bank: &str) -> Option<u128> {
+    max_subsequence_number(bank.as_bytes(), 12)
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    Some(parse(input).map(find_max_joltage_part_two).sum())
+/// Sums the largest joltage of every bank. Returns `None` if a bank has fewer than `num_batteries`
+/// batteries.
+fn total_joltage(input: &str, num_batteries: usize) -> Option<u64> {
+    let total = parse(input)
+        .map(|bank| max_subsequence_number(bank.as_bytes(), num_batteries))
+        .sum::<Option<u128>>()?;
+    u64::try_from(total).ok()
 }
 
-fn find_max_joltage_part_two(s: &str) -> u64 {
-    const OUTPUT_DIGITS: usize = 12;
-    let bytes = s.as_bytes();
-    let mut digits: [_; OUTPUT_DIGITS] = [0; OUTPUT_DIGITS];
-    let mut digit_indices: [_; OUTPUT_DIGITS] = [0; OUTPUT_DIGITS];
-
-    for d in 0..OUTPUT_DIGITS {
-        let start = if d == 0 { 0 } else { digit_indices[d - 1] + 1 };
-        let end = bytes.len() - OUTPUT_DIGITS + d + 1;
-        for (i, b) in bytes[start..end].iter().enumerate() {
-            let num = b - b'0';
-            if num > digits[d] {
-                digits[d] = num;
-                digit_indices[d] = start + i;
-            }
+/// The largest number formed by `k` of the ASCII digits in `bytes`, keeping their order.
+///
+/// Keeps a stack of the digits chosen so far: a new digit pops every smaller digit below it, as
+/// long as enough digits remain to still pick `k` in total. Each digit is pushed and popped at most
+/// once, so this runs in O(n) regardless of `k`. Returns `None` if there are fewer than `k` digits
+/// or the result doesn't fit in a `u128`.
+fn max_subsequence_number(bytes: &[u8], k: usize) -> Option<u128> {
+    let mut droppable = bytes.len().checked_sub(k)?;
+    let mut stack = Vec::with_capacity(bytes.len());
+
+    for &b in bytes {
+        assert!(b.is_ascii_digit(), "invalid battery {:?}", b as char);
+        while droppable > 0 && stack.last().is_some_and(|&top| top < b) {
+            stack.pop();
+            droppable -= 1;
         }
+        stack.push(b);
     }
 
-    digits.iter().fold(0, |acc, digit| acc * 10 + *digit as u64)
+    stack[..k].iter().try_fold(0_u128, |num, &b| {
+        num.checked_mul(10)?.checked_add((b - b'0') as u128)
+    })
 }
 
 fn parse(input: &str) -> impl Iterator<Item = &str> + '_ {
@@ -60,6 +56,19 @@ fn parse(input: &str) -> impl Iterator<Item = &str> + '_ {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::generate::Rng;
+
+    /// Tries every way to pick `k` digits.
+    fn max_subsequence_by_search(bytes: &[u8], k: usize) -> Option<u128> {
+        (0_u32..1 << bytes.len())
+            .filter(|mask| mask.count_ones() as usize == k)
+            .map(|mask| {
+                (0..bytes.len())
+                    .filter(|i| mask >> i & 1 == 1)
+                    .fold(0, |num, i| num * 10 + (bytes[i] - b'0') as u128)
+            })
+            .max()
+    }
 
     #[test]
     fn test_part_one() {
@@ -72,4 +81,36 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(3121910778619));
     }
+
+    #[test]
+    fn test_edge_cases() {
+        assert_eq!(max_subsequence_number(b"12", 3), None);
+        assert_eq!(max_subsequence_number(b"12", 0), Some(0));
+        assert_eq!(max_subsequence_number(b"987", 3), Some(987));
+        assert_eq!(max_subsequence_number(b"1119", 2), Some(19));
+        assert_eq!(
+            max_subsequence_number(&[b'9'; 38], 38),
+            Some(10_u128.pow(38) - 1)
+        );
+        assert_eq!(max_subsequence_number(&[b'9'; 39], 39), None);
+    }
+
+    #[test]
+    fn test_stack_matches_search() {
+        let mut rng = Rng::new(41);
+
+        for _ in 0..500 {
+            let len = rng.index(13);
+            // few distinct digits, so that ties are common.
+            let bytes: Vec<u8> = (0..len).map(|_| b'0' + rng.below(4) as u8 * 3).collect();
+            for k in 0..=len {
+                assert_eq!(
+                    max_subsequence_number(&bytes, k),
+                    max_subsequence_by_search(&bytes, k),
+                    "{} with k = {k}",
+                    String::from_utf8_lossy(&bytes)
+                );
+            }
+        }
+    }
 }
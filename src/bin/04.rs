@@ -1,99 +1,115 @@
+use advent_of_code::grid::Grid;
+use advent_of_code::viz::{self, Colour, Frame};
+
 advent_of_code::solution!(4);
 
-struct TpMap {
-    has_tp: Vec<Vec<bool>>,
-    width: usize,
-    height: usize,
-}
+/// Cells holding a roll of paper.
+type TpMap = Grid<bool>;
 
 pub fn part_one(input: &str) -> Option<u64> {
     let map = parse(input);
-    let mut num_accessible_rolls = 0;
-    for y in 0..map.height {
-        for x in 0..map.width {
-            if !map.has_tp[y][x] {
-                continue;
-            }
+    let num_accessible_rolls = map
+        .positions()
+        .filter(|&pos| map[pos] && is_accessible(&map, pos))
+        .count();
+    Some(num_accessible_rolls as u64)
+}
 
-            if is_accessible(&map, x, y) {
-                num_accessible_rolls += 1;
-            }
+pub fn part_two(input: &str) -> Option<u64> {
+    let map = parse(input);
+    let rounds = removal_rounds(&map);
+    if viz::enabled() {
+        show_rounds(&map, &rounds);
+    }
+    let num_accessible_rolls = rounds.iter().map(Vec::len).sum::<usize>();
+    Some(num_accessible_rolls as u64)
+}
+
+/// Plays the rounds back: rolls removed in the current round are red, earlier ones are faded.
+fn show_rounds(map: &TpMap, rounds: &[Vec<(usize, usize)>]) {
+    let mut removed_in = map.map(|_| None);
+    for (round, positions) in rounds.iter().enumerate() {
+        for &pos in positions {
+            removed_in[pos] = Some(round);
         }
     }
-    Some(num_accessible_rolls)
+
+    for (round, positions) in rounds.iter().enumerate() {
+        viz::frame(|| {
+            Frame::grid(&removed_in, |pos, removed| match removed {
+                _ if !map[pos] => ('.', None),
+                Some(r) if *r < round => ('.', Some(Colour::Grey)),
+                Some(r) if *r == round => ('x', Some(Colour::Red)),
+                _ => ('@', Some(Colour::Green)),
+            })
+            .caption(format_args!(
+                "round {}: removed {} rolls",
+                round + 1,
+                positions.len()
+            ))
+        });
+    }
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let mut map = parse(input);
-    let mut num_accessible_rolls = 0;
+/// Peels accessible rolls off the map round by round and returns the `(x, y)` positions removed in
+/// each round, in row-major order.
+///
+/// Every roll tracks how many neighbouring rolls it has. Removing a roll only decrements its eight
+/// neighbours, and a neighbour is scheduled for the next round the moment its count drops below
+/// four, so the map is never rescanned.
+fn removal_rounds(map: &TpMap) -> Vec<Vec<(usize, usize)>> {
+    let mut has_tp = map.cells().to_vec();
+    let mut neighbour_counts = vec![0_u8; has_tp.len()];
+    let mut current = Vec::new();
+
+    for pos in map.positions().filter(|&pos| map[pos]) {
+        let count = map.neighbours(pos).filter(|&n| map[n]).count() as u8;
+        neighbour_counts[map.index_of(pos)] = count;
+        if count < 4 {
+            current.push(map.index_of(pos));
+        }
+    }
 
-    loop {
-        let mut positions_to_remove = Vec::new();
+    let mut rounds = Vec::new();
+    while !current.is_empty() {
+        let mut next = Vec::new();
 
-        for y in 0..map.height {
-            for x in 0..map.width {
-                if !map.has_tp[y][x] {
+        for &idx in &current {
+            has_tp[idx] = false;
+        }
+        for &idx in &current {
+            for neighbour in map.neighbours(map.position(idx)) {
+                let neighbour = map.index_of(neighbour);
+                if !has_tp[neighbour] {
                     continue;
                 }
-
-                if is_accessible(&map, x, y) {
-                    positions_to_remove.push((x, y));
+                neighbour_counts[neighbour] -= 1;
+                if neighbour_counts[neighbour] == 3 {
+                    next.push(neighbour);
                 }
             }
         }
 
-        if positions_to_remove.is_empty() {
-            break;
-        }
-
-        for (x, y) in positions_to_remove {
-            map.has_tp[y][x] = false;
-            num_accessible_rolls += 1;
-        }
+        current.sort_unstable();
+        rounds.push(current.iter().map(|&idx| map.position(idx)).collect());
+        current = next;
     }
 
-    Some(num_accessible_rolls)
+    rounds
 }
 
-fn is_accessible(map: &TpMap, x: usize, y: usize) -> bool {
-    let mut count = 0;
-
-    let start_y = y.saturating_sub(1);
-    let end_y = (y + 1).min(map.height - 1);
-    let start_x = x.saturating_sub(1);
-    let end_x = (x + 1).min(map.width - 1);
-
-    for new_y in start_y..=end_y {
-        for new_x in start_x..=end_x {
-            if new_y == y && new_x == x || !map.has_tp[new_y][new_x] {
-                continue;
-            }
-            count += 1;
-            if count >= 4 {
-                return false;
-            }
-        }
-    }
-    true
+fn is_accessible(map: &TpMap, pos: (usize, usize)) -> bool {
+    map.neighbours(pos).filter(|&n| map[n]).nth(3).is_none()
 }
 
 fn parse(input: &str) -> TpMap {
-    let has_tp = input
-        .lines()
-        .map(|line| line.bytes().map(|ch| ch == b'@').collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-    let width = has_tp[0].len();
-    let height = has_tp.len();
-    TpMap {
-        has_tp,
-        width,
-        height,
-    }
+    Grid::parse(input, |ch| ch == b'@').unwrap_or_else(|err| panic!("invalid map: {err}"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::generate::{self, Rng};
 
     #[test]
     fn test_part_one() {
@@ -106,4 +122,53 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(43));
     }
+
+    #[test]
+    fn test_removal_rounds_match_rescan() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let generated = (0..3).map(|seed| generate::input(DAY, 0.25, seed).unwrap());
+        // small maps of every density, from a few scattered rolls to long cascades.
+        let mut rng = Rng::new(34);
+        let random = (0..200).map(|_| {
+            let (width, height) = (rng.between(1, 20), rng.between(1, 20));
+            let density = rng.between(10, 95) as f64 / 100.0;
+            let row = |rng: &mut Rng| {
+                let cells = (0..width).map(|_| if rng.chance(density) { '@' } else { '.' });
+                cells.chain(['\n']).collect::<String>()
+            };
+            (0..height).map(|_| row(&mut rng)).collect::<String>()
+        });
+
+        for input in [example].into_iter().chain(generated).chain(random) {
+            let map = parse(&input);
+            assert_eq!(
+                removal_rounds(&map),
+                removal_rounds_by_rescan(parse(&input)),
+                "{input}"
+            );
+        }
+    }
+
+    /// Reference implementation that rescans the whole map every round.
+    fn removal_rounds_by_rescan(mut map: TpMap) -> Vec<Vec<(usize, usize)>> {
+        let mut rounds = Vec::new();
+
+        loop {
+            let positions_to_remove: Vec<_> = map
+                .positions()
+                .filter(|&pos| map[pos] && is_accessible(&map, pos))
+                .collect();
+
+            if positions_to_remove.is_empty() {
+                break;
+            }
+
+            for &pos in &positions_to_remove {
+                map[pos] = false;
+            }
+            rounds.push(positions_to_remove);
+        }
+
+        rounds
+    }
 }
@@ -1,3 +1,6 @@
+use advent_of_code::parse::{ParseError, parse_lines, sections};
+use advent_of_code::scan;
+
 advent_of_code::solution!(5);
 
 struct ParsedInput {
@@ -6,76 +9,81 @@ struct ParsedInput {
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let ParsedInput { mut ranges, ids } = parse(input);
-    let merged_ranges = merge_ranges(&mut ranges);
-    let result = ids
-        .into_iter()
-        .filter(|id| is_fresh(&merged_ranges, id))
-        .count();
-    Some(result as u64)
+    let ParsedInput { ranges, mut ids } = parse(input);
+    let merged_ranges = merge_ranges(ranges);
+    ids.sort_unstable();
+    Some(count_fresh(&merged_ranges, &ids) as u64)
 }
+
 pub fn part_two(input: &str) -> Option<u64> {
-    let ParsedInput { mut ranges, .. } = parse(input);
+    let ParsedInput { ranges, .. } = parse(input);
 
-    let result = merge_ranges(&mut ranges)
+    // a single range can hold all 2^64 IDs, so add up in a wider type.
+    let total: u128 = merge_ranges(ranges)
         .iter()
-        .map(|&(start, end)| end - start + 1)
+        .map(|&(start, end)| (end - start) as u128 + 1)
         .sum();
-    Some(result)
+    u64::try_from(total).ok()
 }
 
-fn merge_ranges(ranges: &mut [(u64, u64)]) -> Vec<(u64, u64)> {
-    ranges.sort();
-    let mut ranges_iter = ranges.iter();
-    let mut merged_ranges: Vec<(u64, u64)> = vec![*ranges_iter.next().unwrap()];
-    for &(start, end) in ranges_iter {
-        let (prev_start, prev_end) = *merged_ranges.last().unwrap();
-        if start >= prev_start && start <= prev_end {
-            merged_ranges.pop();
-            merged_ranges.push((prev_start, prev_end.max(end)));
-        } else {
-            merged_ranges.push((start, end));
+/// Sorts the inclusive ranges and joins those that overlap or touch, leaving disjoint ranges with
+/// gaps between them.
+fn merge_ranges(mut ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    ranges.sort_unstable();
+    let mut merged_ranges: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged_ranges.last_mut() {
+            // `prev_end + 1` would overflow for a range ending at `u64::MAX`.
+            Some((_, prev_end)) if start <= prev_end.saturating_add(1) => {
+                *prev_end = (*prev_end).max(end);
+            }
+            _ => merged_ranges.push((start, end)),
         }
     }
     merged_ranges
 }
 
-fn is_fresh(ranges: &[(u64, u64)], ids: &u64) -> bool {
-    ranges
+/// Counts the IDs covered by any of the ranges, walking both sorted lists once.
+fn count_fresh(merged_ranges: &[(u64, u64)], sorted_ids: &[u64]) -> usize {
+    let mut ranges = merged_ranges.iter().peekable();
+    sorted_ids
         .iter()
-        .any(|(start, end)| (start..=end).contains(&ids))
+        .filter(|&&id| {
+            while ranges.next_if(|&&(_, end)| end < id).is_some() {}
+            ranges.peek().is_some_and(|&&(start, _)| start <= id)
+        })
+        .count()
 }
 
 fn parse(input: &str) -> ParsedInput {
-    let input = input.replace("\r\n", "\n");
-    let sections: Vec<&str> = input.split("\n\n").collect();
-
-    let [ranges_str, ids_str] = sections[..] else {
-        panic!("invalid input");
-    };
-
-    let ranges = ranges_str
-        .lines()
-        .map(|line| {
-            let parts: Vec<u64> = line
-                .split('-')
-                .map(|s| s.parse().expect("Invalid number in range"))
-                .collect();
-            (parts[0], parts[1])
-        })
-        .collect();
-
-    let ids = ids_str
-        .lines()
-        .map(|line| line.parse().expect("Invalid ID number"))
-        .collect();
+    let mut sections = sections(input);
+    let ranges = parse_ranges(sections.next().unwrap_or_default())
+        .unwrap_or_else(|err| panic!("invalid range: {err}"));
+    let ids = parse_lines(sections.next().unwrap_or_default(), |line| {
+        scan!(line, "{}" => u64).map(|(id,)| id)
+    })
+    .unwrap_or_else(|err| panic!("invalid ID: {err}"));
 
     ParsedInput { ranges, ids }
 }
 
+fn parse_ranges(input: &str) -> Result<Vec<(u64, u64)>, ParseError> {
+    parse_lines(input, |line| {
+        let (start, end) = scan!(line, "{}-{}" => u64, u64)?;
+        if start > end {
+            return Err(ParseError::new(
+                1,
+                format!("range {start}-{end} is reversed"),
+            ));
+        }
+        Ok((start, end))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::generate::Rng;
 
     #[test]
     fn test_part_one() {
@@ -88,4 +96,68 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(14));
     }
+
+    #[test]
+    fn test_crlf_input() {
+        let input = advent_of_code::template::read_file("examples", DAY).replace('\n', "\r\n");
+        assert_eq!(part_one(&input), Some(3));
+        assert_eq!(part_two(&input), Some(14));
+    }
+
+    #[test]
+    fn test_merge_ranges() {
+        // overlapping
+        assert_eq!(merge_ranges(vec![(5, 10), (1, 6)]), [(1, 10)]);
+        // adjacent, but not ranges with a gap of one ID
+        assert_eq!(merge_ranges(vec![(4, 6), (1, 3), (8, 9)]), [(1, 6), (8, 9)]);
+        // nested, including a duplicate
+        assert_eq!(
+            merge_ranges(vec![(1, 20), (3, 4), (3, 4), (20, 20)]),
+            [(1, 20)]
+        );
+        assert_eq!(merge_ranges(vec![]), []);
+    }
+
+    #[test]
+    fn test_ranges_near_the_limits() {
+        let max = u64::MAX;
+        assert_eq!(
+            merge_ranges(vec![(max - 1, max), (max, max), (0, 0)]),
+            [(0, 0), (max - 1, max)]
+        );
+        assert_eq!(count_fresh(&[(0, 0), (max - 1, max)], &[0, 1, max]), 2);
+
+        let input = format!("{}-{max}\n0-3\n\n{max}\n", max - 9);
+        assert_eq!(part_one(&input), Some(1));
+        assert_eq!(part_two(&input), Some(14));
+        // every possible ID is one more than fits in a u64.
+        assert_eq!(part_two(&format!("0-{max}\n\n1\n")), None);
+    }
+
+    #[test]
+    fn test_merge_walk_matches_scan() {
+        let mut rng = Rng::new(39);
+
+        for _ in 0..200 {
+            let ranges: Vec<(u64, u64)> = (0..rng.below(8))
+                .map(|_| {
+                    let start = rng.below(50);
+                    (start, start + rng.below(10))
+                })
+                .collect();
+            let mut ids: Vec<u64> = (0..rng.below(20)).map(|_| rng.below(70)).collect();
+
+            let expected = ids
+                .iter()
+                .filter(|&&id| {
+                    ranges
+                        .iter()
+                        .any(|&(start, end)| (start..=end).contains(&id))
+                })
+                .count();
+            let merged_ranges = merge_ranges(ranges);
+            ids.sort_unstable();
+            assert_eq!(count_fresh(&merged_ranges, &ids), expected);
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::ops::Range;
 
 advent_of_code::solution!(6);
 
@@ -9,124 +9,133 @@ enum Operation {
 }
 
 impl Operation {
-    fn run(&self, nums: &[u64]) -> u64 {
-        match self {
-            Operation::Add => nums.iter().sum(),
-            Operation::Mul => nums.iter().product(),
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'+' => Some(Operation::Add),
+            b'*' => Some(Operation::Mul),
+            _ => None,
         }
     }
-}
 
-impl FromStr for Operation {
-    type Err = String;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "+" => Ok(Operation::Add),
-            "*" => Ok(Operation::Mul),
-            _ => unreachable!(),
+    fn run(&self, nums: impl Iterator<Item = u64>) -> u64 {
+        match self {
+            Operation::Add => nums.sum(),
+            Operation::Mul => nums.product(),
         }
     }
 }
 
-struct ParsedInput {
-    numbers: Vec<Vec<u64>>,
-    operations: Vec<Operation>,
+#[derive(Debug)]
+struct Problem {
+    operation: Operation,
+    /// Columns of the worksheet the problem occupies, starting at its operator.
+    columns: Range<usize>,
+}
+
+/// The number rows above the operator row, borrowed from the input, and the problems laid out on
+/// them. Rows may be of different lengths; missing bytes at the end of a row read as spaces.
+struct Worksheet<'a> {
+    rows: Vec<&'a [u8]>,
+    problems: Vec<Problem>,
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let ParsedInput {
-        numbers,
-        operations,
-    } = parse_part_one(input);
+    let worksheet = parse(input);
 
-    let result = calculate(numbers, operations);
+    let result = worksheet
+        .problems
+        .iter()
+        .map(|problem| {
+            let nums = worksheet
+                .rows
+                .iter()
+                .filter_map(|row| read_number(clamped(row, &problem.columns).iter().copied()));
+            problem.operation.run(nums)
+        })
+        .sum();
 
     Some(result)
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let ParsedInput {
-        numbers,
-        operations,
-    } = parse_part_two(input);
+    let worksheet = parse(input);
 
-    let result = calculate(numbers, operations);
+    let result = worksheet
+        .problems
+        .iter()
+        .map(|problem| {
+            let nums = problem
+                .columns
+                .clone()
+                .filter_map(|column| read_number(worksheet.column(column)));
+            problem.operation.run(nums)
+        })
+        .sum();
 
     Some(result)
 }
 
-fn calculate(numbers: Vec<Vec<u64>>, operations: Vec<Operation>) -> u64 {
-    numbers
-        .iter()
-        .zip(operations)
-        .map(|(nums, op)| op.run(nums))
-        .sum()
+impl Worksheet<'_> {
+    /// The bytes of one column from top to bottom.
+    fn column(&self, column: usize) -> impl Iterator<Item = u8> + '_ {
+        self.rows
+            .iter()
+            .map(move |row| row.get(column).copied().unwrap_or(b' '))
+    }
 }
 
-fn parse_part_one(input: &str) -> ParsedInput {
-    let mut lines = input.lines().collect::<Vec<_>>();
-    let operations_line = lines.pop().unwrap();
-
-    let numbers = lines
-        .into_iter()
-        .map(|line| {
-            line.split_ascii_whitespace()
-                .map(|s| s.parse().unwrap())
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-
-    // transpose
-    let numbers = (0..numbers[0].len())
-        .map(|i| numbers.iter().map(|arr| arr[i]).collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-
-    let operations = operations_line
-        .split_ascii_whitespace()
-        .map(|s| s.parse().unwrap())
-        .collect::<Vec<_>>();
+/// The part of `row` inside `columns`, which may be cut short or empty for a short row.
+fn clamped<'a>(row: &'a [u8], columns: &Range<usize>) -> &'a [u8] {
+    &row[columns.start.min(row.len())..columns.end.min(row.len())]
+}
 
-    ParsedInput {
-        numbers,
-        operations,
-    }
+/// Reads the digits among `bytes` as one number, ignoring spaces. Returns `None` for blanks.
+fn read_number(bytes: impl Iterator<Item = u8>) -> Option<u64> {
+    bytes.filter(|&b| b != b' ').fold(None, |num, b| {
+        assert!(b.is_ascii_digit(), "invalid digit {:?}", b as char);
+        Some(num.unwrap_or(0) * 10 + (b - b'0') as u64)
+    })
 }
 
-fn parse_part_two(input: &str) -> ParsedInput {
-    let mut lines = input.lines().collect::<Vec<_>>();
-    let operations_line = lines.pop().unwrap();
+fn parse(input: &str) -> Worksheet<'_> {
+    let mut rows: Vec<&[u8]> = input
+        .lines()
+        .map(str::as_bytes)
+        .filter(|line| !line.trim_ascii().is_empty())
+        .collect();
+    let operations_line = rows.pop().unwrap_or_default();
+    let width = rows
+        .iter()
+        .map(|row| row.len())
+        .fold(operations_line.len(), usize::max);
 
-    // transpose
-    let numbers = (0..lines[0].len())
-        .map(|i| {
-            let s = lines
-                .iter()
-                .map(|arr| arr.as_bytes()[i] as char)
-                .collect::<String>();
-            s.trim().to_owned()
+    // every problem starts at its operator and runs up to the next one.
+    let operators: Vec<(usize, Operation)> = operations_line
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| !b.is_ascii_whitespace())
+        .map(|(column, &b)| {
+            let operation = Operation::from_byte(b)
+                .unwrap_or_else(|| panic!("invalid operation {:?}", b as char));
+            (column, operation)
         })
-        .collect::<Vec<_>>();
-
-    // parse
-    let numbers = numbers
-        .split(|s| s.is_empty())
-        .map(|x| {
-            x.iter()
-                .map(|s| s.parse::<u64>().unwrap())
-                .collect::<Vec<_>>()
+        .collect();
+    let ends: Vec<usize> = operators
+        .iter()
+        .skip(1)
+        .map(|&(column, _)| column)
+        .chain([width])
+        .collect();
+    let problems = operators
+        .into_iter()
+        .zip(ends)
+        .map(|((start, operation), end)| Problem {
+            operation,
+            columns: start..end,
         })
-        .collect::<Vec<_>>();
+        .collect();
 
-    // same as part 1
-    let operations = operations_line
-        .split_ascii_whitespace()
-        .map(|s| s.parse().unwrap())
-        .collect::<Vec<_>>();
-
-    ParsedInput {
-        numbers,
-        operations,
-    }
+    Worksheet { rows, problems }
 }
 
 #[cfg(test)]
@@ -144,4 +153,21 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(3263827));
     }
+
+    #[test]
+    fn test_ragged_lines() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let stripped = example
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        assert_eq!(part_one(&stripped), Some(4277556));
+        assert_eq!(part_two(&stripped), Some(3263827));
+
+        // the operator row is the shortest line, and the last problem sticks out past it.
+        let input = "1 23\n4 5678\n+ *";
+        assert_eq!(part_one(input), Some(5 + 23 * 5678));
+        assert_eq!(part_two(input), Some(14 + 25 * 36 * 7 * 8));
+    }
 }
@@ -1,70 +1,255 @@
+use advent_of_code::grid::Grid;
+use advent_of_code::viz::{self, Colour, Frame};
+
 advent_of_code::solution!(7);
+advent_of_code::variants! {
+    part_one: [part_one_columns],
+    part_two: [part_two_columns],
+}
+
+/// One bit per column, column `i` being bit `i % 64` of word `i / 64`.
+type Row = Vec<u64>;
+
+struct Manifold {
+    width: usize,
+    start: usize,
+    /// Splitter masks for every row below the start.
+    splitters: Vec<Row>,
+}
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let lines = input.lines().collect::<Vec<_>>();
-    let width = lines[0].len();
-    let mut beams = vec![false; width];
-    let start_column = lines[0].find('S').unwrap();
-    beams[start_column] = true;
-    let mut next_beams;
+    let manifold = parse(input);
+    let mut beams = manifold.row_with(manifold.start);
+    let mut hits = manifold.empty_row();
     let mut split_count = 0;
 
-    for line in lines.into_iter().step_by(2).skip(1) {
-        next_beams = vec![false; width];
+    // cells of the rows traced so far, in row-major order.
+    let mut traced = vec![];
 
-        for (i, &ch) in line.as_bytes().iter().enumerate() {
-            if !beams[i] {
-                continue;
-            }
-            if ch == b'^' {
-                next_beams[i - 1] = true;
-                next_beams[i + 1] = true;
-                split_count += 1;
-            } else {
-                next_beams[i] = true;
-            }
+    for (y, splitters) in manifold.splitters.iter().enumerate() {
+        if viz::enabled() {
+            traced.extend(manifold.render_row(&beams, splitters));
         }
-
-        beams = next_beams;
+        split_count += manifold.split(&mut beams, splitters, &mut hits) as u64;
+        viz::frame(|| {
+            let rows = Grid::from_fn(manifold.width, y + 1, |(x, y)| {
+                traced[y * manifold.width + x]
+            });
+            Frame::grid(&rows, |_, &cell| cell).caption(format_args!("splits: {split_count}"))
+        });
     }
 
     Some(split_count)
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let lines = input.lines().collect::<Vec<_>>();
-    let width = lines[0].len();
-    let mut beams = vec![0; width];
-    let start_column = lines[0].find('S').unwrap();
-    beams[start_column] = 1;
-    let mut next_beams;
+    let manifold = parse(input);
+    let mut beams = manifold.row_with(manifold.start);
+    let mut hits = manifold.empty_row();
+    let mut counts = vec![0_u64; manifold.width];
+    let mut next_counts = counts.clone();
+    counts[manifold.start] = 1;
     let mut timeline_count = 1;
 
-    for line in lines.into_iter().step_by(2).skip(1) {
-        next_beams = vec![0; width];
+    for splitters in &manifold.splitters {
+        if manifold.split(&mut beams, splitters, &mut hits) == 0 {
+            continue;
+        }
 
-        for (i, &ch) in line.as_bytes().iter().enumerate() {
-            if beams[i] == 0 {
-                continue;
+        next_counts.copy_from_slice(&counts);
+        for column in set_bits(&hits) {
+            let timelines = counts[column];
+            next_counts[column] -= timelines;
+            if column > 0 {
+                next_counts[column - 1] += timelines;
             }
-            if ch == b'^' {
-                next_beams[i - 1] += beams[i];
-                next_beams[i + 1] += beams[i];
-                timeline_count += beams[i];
-            } else {
-                next_beams[i] += beams[i];
+            if column + 1 < manifold.width {
+                next_counts[column + 1] += timelines;
             }
+            timeline_count += timelines;
         }
+        std::mem::swap(&mut counts, &mut next_counts);
+    }
 
+    Some(timeline_count)
+}
+
+impl Manifold {
+    fn empty_row(&self) -> Row {
+        vec![0; self.width.div_ceil(64)]
+    }
+
+    fn row_with(&self, column: usize) -> Row {
+        let mut row = self.empty_row();
+        row[column / 64] |= 1 << (column % 64);
+        row
+    }
+
+    /// Moves `beams` past one row of splitters, leaving the splitters that were hit in `hits`.
+    /// Returns the number of splits.
+    fn split(&self, beams: &mut Row, splitters: &Row, hits: &mut Row) -> u32 {
+        let mut split_count = 0;
+        for ((hit, beam), splitter) in hits.iter_mut().zip(beams.iter()).zip(splitters) {
+            *hit = beam & splitter;
+            split_count += hit.count_ones();
+        }
+        if split_count == 0 {
+            return 0;
+        }
+
+        // a split beam continues one column to either side, carrying bits across words.
+        let mut carry_right = 0;
+        for idx in 0..beams.len() {
+            let carry_left = hits.get(idx + 1).map_or(0, |next| next << 63);
+            beams[idx] = (beams[idx] & !hits[idx])
+                | hits[idx] << 1
+                | carry_right
+                | hits[idx] >> 1
+                | carry_left;
+            carry_right = hits[idx] >> 63;
+        }
+        // beams leaving the grid on the right are lost.
+        if !self.width.is_multiple_of(64)
+            && let Some(last) = beams.last_mut()
+        {
+            *last &= (1 << (self.width % 64)) - 1;
+        }
+
+        split_count
+    }
+
+    /// A row of splitters with the beams arriving at it: splitters that are hit light up.
+    fn render_row(
+        &self,
+        beams: &Row,
+        splitters: &Row,
+    ) -> impl Iterator<Item = (char, Option<Colour>)> {
+        let bit = |row: &Row, column: usize| row[column / 64] >> (column % 64) & 1 == 1;
+        (0..self.width).map(
+            move |column| match (bit(beams, column), bit(splitters, column)) {
+                (true, true) => ('^', Some(Colour::Yellow)),
+                (false, true) => ('^', Some(Colour::Grey)),
+                (true, false) => ('|', Some(Colour::Cyan)),
+                (false, false) => (' ', None),
+            },
+        )
+    }
+}
+
+fn set_bits(row: &Row) -> impl Iterator<Item = usize> + '_ {
+    row.iter().enumerate().flat_map(|(w, &word)| {
+        let mut rest = word;
+        std::iter::from_fn(move || {
+            if rest == 0 {
+                return None;
+            }
+            let bit = rest.trailing_zeros() as usize;
+            rest &= rest - 1;
+            Some(w * 64 + bit)
+        })
+    })
+}
+
+/// [`part_one`] with one column at a time instead of bitsets.
+fn part_one_columns(input: &str) -> Option<u64> {
+    Some(solve_by_columns(input).0)
+}
+
+/// [`part_two`] with one column at a time instead of bitsets.
+fn part_two_columns(input: &str) -> Option<u64> {
+    Some(solve_by_columns(input).1)
+}
+
+/// Propagates the beams one column at a time, counting splits and timelines per column.
+fn solve_by_columns(input: &str) -> (u64, u64) {
+    let lines = input.lines().collect::<Vec<_>>();
+    let width = lines.iter().map(|line| line.len()).max().unwrap();
+    let mut beams = vec![0_u64; width];
+    beams[lines[0].find('S').unwrap()] = 1;
+    let (mut split_count, mut timeline_count) = (0, 1);
+
+    for line in &lines[1..] {
+        let mut next_beams = vec![0_u64; width];
+        for (i, &timelines) in beams.iter().enumerate().filter(|(_, t)| **t > 0) {
+            if line.as_bytes().get(i) == Some(&b'^') {
+                if i > 0 {
+                    next_beams[i - 1] += timelines;
+                }
+                if i + 1 < width {
+                    next_beams[i + 1] += timelines;
+                }
+                split_count += 1;
+                timeline_count += timelines;
+            } else {
+                next_beams[i] += timelines;
+            }
+        }
         beams = next_beams;
     }
 
-    Some(timeline_count)
+    (split_count, timeline_count)
+}
+
+fn parse(input: &str) -> Manifold {
+    let mut lines = input.lines();
+    let first = lines.next().unwrap_or_default();
+    let start = first
+        .find('S')
+        .unwrap_or_else(|| panic!("invalid manifold: no start in {first:?}"));
+    let lines: Vec<&str> = lines.collect();
+    let width = lines
+        .iter()
+        .map(|line| line.len())
+        .fold(first.len(), usize::max);
+
+    let splitters = lines
+        .into_iter()
+        .map(|line| {
+            let mut row = vec![0; width.div_ceil(64)];
+            for (word, chunk) in row.iter_mut().zip(line.as_bytes().chunks(64)) {
+                *word = splitter_mask(chunk);
+            }
+            row
+        })
+        .filter(|row| row.iter().any(|&word| word != 0))
+        .collect();
+
+    Manifold {
+        width,
+        start,
+        splitters,
+    }
+}
+
+/// Marks the `^` among up to 64 bytes, comparing eight at a time within a single word.
+fn splitter_mask(bytes: &[u8]) -> u64 {
+    const LOW: u64 = 0x7f7f_7f7f_7f7f_7f7f;
+    let compare = |word: u64| {
+        let diff = word ^ u64::from_le_bytes([b'^'; 8]);
+        // the high bit of every byte that is zero in `diff`, without carries between bytes.
+        let zero_bytes = !(((diff & LOW) + LOW) | diff | LOW);
+        // gather the eight high bits into the top byte, in byte order.
+        (zero_bytes >> 7).wrapping_mul(0x0102_0408_1020_4080) >> 56
+    };
+
+    let mut chunks = bytes.chunks_exact(8);
+    let mut mask = 0;
+    for (idx, chunk) in chunks.by_ref().enumerate() {
+        mask |= compare(u64::from_le_bytes(chunk.try_into().unwrap())) << (8 * idx);
+    }
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        let mut padded = [0; 8];
+        padded[..rest.len()].copy_from_slice(rest);
+        mask |= compare(u64::from_le_bytes(padded)) << (bytes.len() - rest.len());
+    }
+    mask
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::generate::{self, Rng};
 
     #[test]
     fn test_part_one() {
@@ -77,4 +262,22 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(40));
     }
+
+    #[test]
+    fn test_bitsets_match_columns() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let mut inputs = vec![example];
+        // widths around word boundaries, and splitters on both edges. Much wider manifolds are
+        // also taller and have more timelines than a u64 holds.
+        for (seed, width) in [(1, 63), (2, 64), (3, 65), (4, 128), (5, 130)] {
+            inputs.push(generate::manifold_of_width(&mut Rng::new(seed), width));
+        }
+        inputs.push("S.\n^.\n.^\n".to_string());
+
+        for input in inputs {
+            let (split_count, timeline_count) = solve_by_columns(&input);
+            assert_eq!(part_one(&input), Some(split_count), "{input}");
+            assert_eq!(part_two(&input), Some(timeline_count), "{input}");
+        }
+    }
 }
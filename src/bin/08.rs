@@ -1,32 +1,18 @@
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 
-advent_of_code::solution!(8);
-
-const NUM_CONNECTIONS: usize = if cfg!(test) { 10 } else { 1000 };
+use advent_of_code::geom::Point3;
+use advent_of_code::record::{Scene, Shape};
+use advent_of_code::spatial::KdTree;
+use advent_of_code::viz::{self, Colour, Frame};
 
-struct Point {
-    pub x: i64,
-    pub y: i64,
-    pub z: i64,
+advent_of_code::solution!(8);
+advent_of_code::variants! {
+    part_one: [part_one_all_pairs],
+    part_two: [part_two_all_pairs],
 }
 
-impl Point {
-    #[inline]
-    fn new(x: i64, y: i64, z: i64) -> Point {
-        Point { x, y, z }
-    }
-
-    /// Calculate squared distance to avoid expensive sqrt operation.
-    /// Since sqrt preserves ordering, comparing squared distances gives the same result.
-    #[inline]
-    fn distance_squared(&self, p: &Point) -> i64 {
-        let dx = self.x - p.x;
-        let dy = self.y - p.y;
-        let dz = self.z - p.z;
-        dx * dx + dy * dy + dz * dz
-    }
-}
+const NUM_CONNECTIONS: usize = if cfg!(test) { 10 } else { 1000 };
 
 #[derive(Debug, Clone)]
 pub struct UnionFind {
@@ -105,10 +91,10 @@ impl UnionFind {
 
 pub fn part_one(input: &str) -> Option<u64> {
     let points = parse(input);
-    let distances = calculate_distances_partial(&points, NUM_CONNECTIONS);
+    let tree = KdTree::new(points.iter().map(|p| [p.x, p.y, p.z]));
     let mut uf = UnionFind::new(points.len());
 
-    for (_distance, i, j) in distances {
+    for (_distance, i, j) in tree.closest_pairs().take(NUM_CONNECTIONS) {
         uf.union(i, j);
     }
 
@@ -122,67 +108,124 @@ pub fn part_one(input: &str) -> Option<u64> {
 
 pub fn part_two(input: &str) -> Option<u64> {
     let points = parse(input);
-    let distances = calculate_distances(&points);
+    let tree = KdTree::new(points.iter().map(|p| [p.x, p.y, p.z]));
     let mut uf = UnionFind::new(points.len());
-
-    let mut heap = distances
-        .into_iter()
-        .map(Reverse)
-        .collect::<BinaryHeap<_>>();
-
-    while let Some(Reverse((_distance, i, j))) = heap.pop() {
-        uf.union(i, j);
+    let mut connections = vec![];
+    let frame_every = (points.len() / 50).max(1);
+
+    for (_distance, i, j) in tree.closest_pairs() {
+        if uf.union(i, j) && viz::enabled() {
+            connections.push((i, j));
+            let components = uf.count_components();
+            if components.is_multiple_of(frame_every) || components == 1 {
+                viz::frame(|| {
+                    Frame::scene(draw_circuits(&points, &connections, &mut uf))
+                        .caption(format_args!("circuits: {components}"))
+                });
+            }
+        }
         if uf.count_components() == 1 {
             return Some((points[i].x * points[j].x) as u64);
         }
     }
 
-    unreachable!()
+    // a single junction box has no connection to report.
+    None
 }
 
-fn calculate_distances(points: &[Point]) -> Vec<(i64, usize, usize)> {
-    let mut distances = Vec::with_capacity((points.len() * (points.len() - 1)) / 2);
+/// The junction boxes seen from above, coloured by circuit, with lone boxes in grey.
+fn draw_circuits(
+    points: &[Point3<i64>],
+    connections: &[(usize, usize)],
+    uf: &mut UnionFind,
+) -> Scene {
+    const CIRCUIT_COLOURS: [Colour; 6] = [
+        Colour::Red,
+        Colour::Green,
+        Colour::Yellow,
+        Colour::Blue,
+        Colour::Magenta,
+        Colour::Cyan,
+    ];
+    let position = |idx: usize| (points[idx].x as f64, points[idx].y as f64);
+
+    let mut scene = Scene::around((0..points.len()).map(position));
+    for &(i, j) in connections {
+        scene.push(Shape::Line {
+            from: position(i),
+            to: position(j),
+            colour: Colour::Grey,
+        });
+    }
+    for idx in 0..points.len() {
+        let root = uf.find(idx);
+        let colour = if uf.size_of(root) == 1 {
+            Colour::Grey
+        } else {
+            CIRCUIT_COLOURS[root % CIRCUIT_COLOURS.len()]
+        };
+        scene.push(Shape::Dot {
+            centre: position(idx),
+            colour,
+        });
+    }
+    scene
+}
 
-    for i in 0..points.len() {
-        for j in i + 1..points.len() {
-            let distance = points[i].distance_squared(&points[j]);
-            distances.push((distance, i, j));
+/// [`part_one`] with the closest pairs picked from all pairs of junction boxes instead of the
+/// k-d tree.
+fn part_one_all_pairs(input: &str) -> Option<u64> {
+    let points = parse(input);
+    let mut uf = UnionFind::new(points.len());
+
+    // the closest pairs so far, farthest on top.
+    let mut closest = BinaryHeap::with_capacity(NUM_CONNECTIONS);
+    for pair in all_pairs(&points) {
+        if closest.len() < NUM_CONNECTIONS {
+            closest.push(pair);
+        } else if closest.peek().is_some_and(|farthest| pair < *farthest) {
+            closest.pop();
+            closest.push(pair);
         }
     }
+    for (_distance, i, j) in closest {
+        uf.union(i, j);
+    }
 
-    distances
+    let mut sizes: Vec<u64> = uf.roots().map(|root| uf.size_of(root) as u64).collect();
+    sizes.select_nth_unstable_by(2, |a, b| b.cmp(a));
+    Some(sizes[0] * sizes[1] * sizes[2])
 }
 
-fn calculate_distances_partial(points: &[Point], limit: usize) -> Vec<(i64, usize, usize)> {
-    let mut heap = BinaryHeap::with_capacity(limit + 1);
-
-    for i in 0..points.len() {
-        for j in i + 1..points.len() {
-            let distance = points[i].distance_squared(&points[j]);
+/// [`part_two`] with all pairs of junction boxes in a heap instead of the k-d tree.
+fn part_two_all_pairs(input: &str) -> Option<u64> {
+    let points = parse(input);
+    let mut uf = UnionFind::new(points.len());
 
-            if heap.len() < limit {
-                heap.push((distance, i, j));
-            } else if let Some(&(max_dist, _, _)) = heap.peek()
-                && distance < max_dist
-            {
-                heap.pop();
-                heap.push((distance, i, j));
-            }
+    let mut heap: BinaryHeap<_> = all_pairs(&points).map(Reverse).collect();
+    while let Some(Reverse((_distance, i, j))) = heap.pop() {
+        uf.union(i, j);
+        if uf.count_components() == 1 {
+            return Some((points[i].x * points[j].x) as u64);
         }
     }
+    None
+}
 
-    let mut result = heap.into_vec();
-    result.sort_unstable();
-    result
+/// Every pair of points with their squared distance.
+fn all_pairs(points: &[Point3<i64>]) -> impl Iterator<Item = (i64, usize, usize)> + '_ {
+    (0..points.len()).flat_map(move |i| {
+        (i + 1..points.len()).map(move |j| (points[i].distance_squared(points[j]), i, j))
+    })
 }
 
-fn parse(input: &str) -> Vec<Point> {
+fn parse(input: &str) -> Vec<Point3<i64>> {
     input
         .lines()
         .map(|line| {
             let coords: Vec<i64> = line.split(',').map(|x| x.parse().unwrap()).collect();
 
-            Point::new(coords[0], coords[1], coords[2])
+            Point3::new(coords[0], coords[1], coords[2])
         })
         .collect()
 }
@@ -202,4 +245,10 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(25272));
     }
+
+    #[test]
+    fn test_single_box_has_no_last_connection() {
+        assert_eq!(part_two("162,817,812\n"), None);
+        assert_eq!(part_two_all_pairs("162,817,812\n"), None);
+    }
 }
@@ -1,181 +1,106 @@
+use advent_of_code::explain::{self, Cell};
+use advent_of_code::geom::{Point2, RectilinearPolygon};
+use advent_of_code::record::{Scene, Shape};
+use advent_of_code::viz::{self, Colour, Frame};
+use std::collections::BinaryHeap;
+
 advent_of_code::solution!(9);
 
 pub fn part_one(input: &str) -> Option<u64> {
     let points = parse(input).collect::<Vec<_>>();
 
-    points
-        .iter().enumerate()
-        .flat_map(|(i, &(x1, y1))| {
-            points[i + 1..].iter()
-                .map(move |&(x2, y2)| {
-                    (x2.abs_diff(x1) + 1) * (y2.abs_diff(y1) + 1)
-                })
+    let (area, a, b) = points
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &a)| {
+            points[i + 1..]
+                .iter()
+                .map(move |&b| (rectangle_area(a, b), a, b))
         })
-        .max()
+        .max_by_key(|&(area, _, _)| area)?;
+    explain_rectangle(a, b, area);
+    Some(area)
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
     let points = parse(input).collect::<Vec<_>>();
-    if points.len() < 2 {
-        return None;
-    }
-
-    // Coordinate compression
-    let mut uniq_x: Vec<u64> = points.iter().map(|&(x, _)| x).collect();
-    uniq_x.sort_unstable();
-    uniq_x.dedup();
-
-    let mut uniq_y: Vec<u64> = points.iter().map(|&(_, y)| y).collect();
-    uniq_y.sort_unstable();
-    uniq_y.dedup();
-
-    let w = uniq_x.len();
-    let h = uniq_y.len();
-    if w == 0 || h == 0 {
-        return None;
-    }
-
-    let mut grid: Vec<Vec<u8>> = vec![vec![b'.'; w]; h];
-
-    let mut z_points: Vec<(usize, usize)> = Vec::with_capacity(points.len());
-    for &(x, y) in &points {
-        let zx = index_of(&uniq_x, x);
-        let zy = index_of(&uniq_y, y);
-        grid[zy][zx] = b'#';
-        z_points.push((zx, zy));
-    }
-
-    for i in 0..z_points.len() {
-        let (ax, ay) = z_points[i];
-        let (bx, by) = z_points[(i + 1) % z_points.len()];
-
-        if ax == bx {
-            let (y0, y1) = if ay <= by { (ay, by) } else { (by, ay) };
-            for y in y0..=y1 {
-                grid[y][ax] = b'#';
-            }
-        } else if ay == by {
-            let (x0, x1) = if ax <= bx { (ax, bx) } else { (bx, ax) };
-            for x in x0..=x1 {
-                grid[ay][x] = b'#';
-            }
-        } else {
-            // No diagonal edges
-            return None;
+    let polygon = RectilinearPolygon::new(&points)?;
+
+    // heapifying is linear, and the first contained rectangle popped is the largest one, so
+    // usually only a handful of candidates are ever ordered.
+    let mut candidates: BinaryHeap<(u64, usize, usize)> = (0..points.len())
+        .flat_map(|i| (i + 1..points.len()).map(move |j| (i, j)))
+        .map(|(i, j)| (rectangle_area(points[i], points[j]), i, j))
+        .collect();
+
+    let mut checked = 0;
+    while let Some((area, i, j)) = candidates.pop() {
+        checked += 1;
+        let contained = polygon.contains_rect(points[i], points[j]);
+        viz::frame(|| {
+            let verdict = if contained { "fits" } else { "sticks out" };
+            Frame::scene(draw_candidate(&points, (points[i], points[j]), contained))
+                .caption(format_args!("area {area} {verdict}"))
+        });
+        if contained {
+            explain_rectangle(points[i], points[j], area);
+            explain::row("search", || [("candidates checked", Cell::from(checked))]);
+            return Some(area);
         }
     }
 
-    let inside = get_inside_point(&grid)?;
-    flood_fill(&mut grid, inside);
-
-    let mut best: u64 = 0;
-    for i in 0..points.len() {
-        for j in (i + 1)..points.len() {
-            let a = points[i];
-            let b = points[j];
-
-            if is_enclosed(a, b, &grid, &uniq_x, &uniq_y) {
-                let area = (a.0.abs_diff(b.0) + 1) * (a.1.abs_diff(b.1) + 1);
-                best = best.max(area);
-            }
-        }
-    }
-
-    Some(best)
+    Some(0)
 }
 
-fn index_of(sorted_uniq: &[u64], v: u64) -> usize {
-    sorted_uniq
-        .binary_search(&v)
-        .expect("value must exist in its own uniq array")
+fn explain_rectangle(a: Point2<u64>, b: Point2<u64>, area: u64) {
+    explain::row("rectangle", || {
+        [
+            ("corner", Cell::from(format!("{},{}", a.x, a.y))),
+            ("opposite corner", format!("{},{}", b.x, b.y).into()),
+            ("area", area.into()),
+        ]
+    });
 }
 
-fn is_enclosed(
-    a: (u64, u64),
-    b: (u64, u64),
-    grid: &[Vec<u8>],
-    uniq_x: &[u64],
-    uniq_y: &[u64],
-) -> bool {
-    let mut x1 = index_of(uniq_x, a.0);
-    let mut x2 = index_of(uniq_x, b.0);
-    if x1 > x2 {
-        std::mem::swap(&mut x1, &mut x2);
-    }
-
-    let mut y1 = index_of(uniq_y, a.1);
-    let mut y2 = index_of(uniq_y, b.1);
-    if y1 > y2 {
-        std::mem::swap(&mut y1, &mut y2);
-    }
-
-    for x in x1..=x2 {
-        if grid[y1][x] == b'.' || grid[y2][x] == b'.' {
-            return false;
-        }
-    }
-    for y in y1..=y2 {
-        if grid[y][x1] == b'.' || grid[y][x2] == b'.' {
-            return false;
+/// The red and green tiles' outline in grey, with a candidate rectangle on top: filled green if it
+/// fits inside, outlined in red if it doesn't.
+fn draw_candidate(
+    points: &[Point2<u64>],
+    (a, b): (Point2<u64>, Point2<u64>),
+    contained: bool,
+) -> Scene {
+    let corner = |p: Point2<u64>| (p.x as f64, p.y as f64);
+    let mut scene = Scene::around(points.iter().map(|&p| corner(p)));
+    scene.push(Shape::Polygon {
+        points: points.iter().map(|&p| corner(p)).collect(),
+        fill: Colour::Grey,
+    });
+
+    let rectangle =
+        [(a.x, a.y), (b.x, a.y), (b.x, b.y), (a.x, b.y)].map(|(x, y)| (x as f64, y as f64));
+    if contained {
+        scene.push(Shape::Polygon {
+            points: rectangle.to_vec(),
+            fill: Colour::Green,
+        });
+    } else {
+        for (k, &from) in rectangle.iter().enumerate() {
+            scene.push(Shape::Line {
+                from,
+                to: rectangle[(k + 1) % 4],
+                colour: Colour::Red,
+            });
         }
     }
-    true
+    scene
 }
 
-fn flood_fill(grid: &mut [Vec<u8>], start: (usize, usize)) {
-    let mut stack = vec![start];
-    let dirs: [(isize, isize); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-
-    while let Some((x, y)) = stack.pop() {
-        if grid[y][x] != b'.' {
-            continue;
-        }
-        grid[y][x] = b'X';
-
-        for (dx, dy) in dirs {
-            let nx = x as isize + dx;
-            let ny = y as isize + dy;
-            if ny >= 0
-                && (ny as usize) < grid.len()
-                && nx >= 0
-                && (nx as usize) < grid[0].len()
-            {
-                let (nxu, nyu) = (nx as usize, ny as usize);
-                if grid[nyu][nxu] == b'.' {
-                    stack.push((nxu, nyu));
-                }
-            }
-        }
-    }
-}
-
-fn get_inside_point(grid: &[Vec<u8>]) -> Option<(usize, usize)> {
-    for y in 0..grid.len() {
-        for x in 0..grid[0].len() {
-            if grid[y][x] != b'.' {
-                continue;
-            }
-
-            let mut hits_left = 0usize;
-            let mut prev = b'.';
-
-            for i in (0..=x).rev() {
-                let cur = grid[y][i];
-                if cur != prev {
-                    hits_left += 1;
-                }
-                prev = cur;
-            }
-
-            if hits_left % 2 == 1 {
-                return Some((x, y));
-            }
-        }
-    }
-    None
+/// Number of tiles covered by the rectangle with opposite corners `a` and `b`.
+fn rectangle_area(a: Point2<u64>, b: Point2<u64>) -> u64 {
+    (a.x.abs_diff(b.x) + 1) * (a.y.abs_diff(b.y) + 1)
 }
 
-fn parse(input: &str) -> impl Iterator<Item = (u64, u64)> {
+fn parse(input: &str) -> impl Iterator<Item = Point2<u64>> {
     input.lines().map(|line| {
         let mut split = line.split(',');
         let (a, b) = match (split.next(), split.next()) {
@@ -183,7 +108,7 @@ fn parse(input: &str) -> impl Iterator<Item = (u64, u64)> {
             _ => panic!(),
         };
         match (a.parse::<u64>(), b.parse::<u64>()) {
-            (Ok(a), Ok(b)) => (a, b),
+            (Ok(a), Ok(b)) => Point2::new(a, b),
             _ => panic!(),
         }
     })
@@ -192,6 +117,53 @@ fn parse(input: &str) -> impl Iterator<Item = (u64, u64)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::generate::{self, Rng};
+    use advent_of_code::geom::{Containment, point_in_polygon};
+
+    /// Part two over every pair of red tiles, with containment decided tile by tile. Edges only
+    /// run along red tile coordinates, so locating one tile on every coordinate and one in every
+    /// gap between neighbouring coordinates locates them all.
+    fn part_two_scan(input: &str) -> Option<u64> {
+        let points = parse(input).collect::<Vec<_>>();
+        let samples = |mut coords: Vec<u64>| {
+            coords.sort_unstable();
+            coords.dedup();
+            let mut samples = vec![];
+            for (i, &c) in coords.iter().enumerate() {
+                samples.push(c);
+                if coords.get(i + 1).is_some_and(|&next| next - c > 1) {
+                    samples.push(c + 1);
+                }
+            }
+            samples
+        };
+        let xs = samples(points.iter().map(|p| p.x).collect());
+        let ys = samples(points.iter().map(|p| p.y).collect());
+        let inside: Vec<Vec<bool>> = (ys.iter())
+            .map(|&y| {
+                (xs.iter())
+                    .map(|&x| point_in_polygon(Point2::new(x, y), &points) != Containment::Outside)
+                    .collect()
+            })
+            .collect();
+
+        let mut best = 0;
+        for (i, &a) in points.iter().enumerate() {
+            for &b in &points[i + 1..] {
+                let column = |x: u64| xs.binary_search(&x).unwrap();
+                let row = |y: u64| ys.binary_search(&y).unwrap();
+                let (x1, x2) = (column(a.x.min(b.x)), column(a.x.max(b.x)));
+                let (y1, y2) = (row(a.y.min(b.y)), row(a.y.max(b.y)));
+                if inside[y1..=y2]
+                    .iter()
+                    .all(|row| row[x1..=x2].iter().all(|&t| t))
+                {
+                    best = best.max(rectangle_area(a, b));
+                }
+            }
+        }
+        Some(best)
+    }
 
     #[test]
     fn test_part_one() {
@@ -204,4 +176,20 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(24));
     }
+
+    #[test]
+    fn test_part_two_matches_scan() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let generated = (0..3).map(|seed| generate::input(DAY, 0.1, seed).unwrap());
+        // the generated inputs are star shaped, so also try outlines with notches and pockets.
+        let mut rng = Rng::new(9);
+        let outlines = (0..200).map(|_| {
+            let vertices = generate::rectilinear_polygon(&mut rng);
+            let lines = vertices.iter().map(|p| format!("{},{}\n", p.x, p.y));
+            lines.collect::<String>()
+        });
+        for input in [example].into_iter().chain(generated).chain(outlines) {
+            assert_eq!(part_two(&input), part_two_scan(&input), "{input}");
+        }
+    }
 }
@@ -1,8 +1,18 @@
+use advent_of_code::explain::{self, Cell};
+use advent_of_code::gf2::{self, BitVec};
+use advent_of_code::ilp;
+use advent_of_code::parse::{ParseError, comma_list, offset_of, parse_lines};
+use advent_of_code::scan;
+use advent_of_code::verify;
 use rayon::prelude::*;
-use z3::ast::Int;
-use z3::{Optimize, SatResult};
 
-advent_of_code::solution!(10);
+advent_of_code::solution!(10, lines: [part_one_line, part_two_line]);
+advent_of_code::variants! {
+    part_two: [
+        #[cfg(feature = "z3")]
+        part_two_z3,
+    ],
+}
 
 #[derive(Debug)]
 struct ParsedInput {
@@ -16,35 +26,227 @@ struct ParsedLine {
     joltage_requirements: Vec<u64>,
 }
 
+/// What pressing the buttons has to achieve.
+#[derive(Debug, Clone, Copy)]
+enum Goal {
+    Lights,
+    Joltages,
+}
+
 pub fn part_one(input: &str) -> Option<u64> {
-    let parsed_input = parse(input);
-    let result = parsed_input
-        .parsed_lines
-        .into_par_iter()
-        .map(find_minimum_switches)
-        .sum::<u32>();
+    let machines = parse(input).parsed_lines;
+    let choices: Vec<Option<BitVec>> = machines.par_iter().map(find_minimum_switches).collect();
+
+    for (idx, (machine, buttons)) in machines.iter().zip(&choices).enumerate() {
+        if let Some(buttons) = buttons {
+            verify::witness(format_args!("machine {}", idx + 1), || {
+                let presses: Vec<u64> = (0..buttons.len()).map(|b| buttons.get(b) as u64).collect();
+                verify_witness(machine, &presses, Goal::Lights)
+            });
+        }
+        explain::row("machines", || {
+            let (presses, buttons) = match buttons {
+                Some(buttons) => {
+                    let pressed = buttons.ones().map(|button| schematic(machine, button));
+                    let pressed = pressed.collect::<Vec<_>>().join(" ");
+                    (Cell::from(buttons.count_ones()), pressed)
+                }
+                None => (Cell::from("-"), "no solution".to_string()),
+            };
+            [
+                ("machine", Cell::from(idx + 1)),
+                ("presses", presses),
+                ("buttons", buttons.into()),
+            ]
+        });
+    }
 
-    Some(result as u64)
+    // a machine whose lights can't be reached leaves the part without an answer.
+    choices
+        .iter()
+        .map(|buttons| Some(buttons.as_ref()?.count_ones() as u64))
+        .sum()
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let parsed_input = parse(input);
-    let result: u64 = parsed_input
-        .parsed_lines
-        .into_par_iter()
-        .map(find_minimum_button_presses_z3)
-        .sum();
+    let machines = parse(input).parsed_lines;
+    let choices: Vec<Option<Vec<u64>>> = machines
+        .par_iter()
+        .map(find_minimum_button_presses)
+        .collect();
+
+    for (idx, (machine, presses)) in machines.iter().zip(&choices).enumerate() {
+        if let Some(presses) = presses {
+            verify::witness(format_args!("machine {}", idx + 1), || {
+                verify_witness(machine, presses, Goal::Joltages)
+            });
+        }
+        explain::row("machines", || {
+            let (total, buttons) = match presses {
+                Some(presses) => {
+                    let pressed = (presses.iter().enumerate())
+                        .filter(|&(_, &count)| count > 0)
+                        .map(|(button, count)| format!("{count}×{}", schematic(machine, button)));
+                    let buttons = pressed.collect::<Vec<_>>().join(" ");
+                    (Cell::from(presses.iter().sum::<u64>()), buttons)
+                }
+                None => (Cell::from("-"), "no solution".to_string()),
+            };
+            [
+                ("machine", Cell::from(idx + 1)),
+                ("presses", total),
+                ("buttons", buttons.into()),
+            ]
+        });
+    }
+
+    // a machine that can't reach its joltages leaves the part without an answer.
+    choices
+        .iter()
+        .map(|presses| Some(presses.as_ref()?.iter().sum::<u64>()))
+        .sum()
+}
+
+fn part_one_line(line: &str) -> Option<usize> {
+    find_minimum_switches(&parse_machine(line)).map(|buttons| buttons.count_ones())
+}
+
+fn part_two_line(line: &str) -> Option<Vec<u64>> {
+    find_minimum_button_presses(&parse_machine(line))
+}
+
+/// A button's wiring schematic as written in the input, e.g. `(0,2)`.
+fn schematic(machine: &ParsedLine, button: usize) -> String {
+    let counters: Vec<String> = machine.wiring_schematics[button]
+        .iter()
+        .map(u64::to_string)
+        .collect();
+    format!("({})", counters.join(","))
+}
+
+/// Checks that pressing every button `presses[button]` times reaches the goal, by replaying the
+/// presses on the machine's wiring rather than trusting the solver's equations.
+fn verify_witness(machine: &ParsedLine, presses: &[u64], goal: Goal) -> Result<(), String> {
+    let buttons = machine.wiring_schematics.len();
+    if presses.len() != buttons {
+        return Err(format!(
+            "{} press counts for {buttons} buttons",
+            presses.len()
+        ));
+    }
+
+    let counters = match goal {
+        Goal::Lights => machine.light_diagram.len(),
+        Goal::Joltages => machine.joltage_requirements.len(),
+    };
+    let mut reached = vec![0; counters];
+    for (button, &count) in presses.iter().enumerate() {
+        for &counter in &machine.wiring_schematics[button] {
+            let Some(value) = reached.get_mut(counter as usize) else {
+                return Err(format!(
+                    "{} is wired to missing {counter}",
+                    schematic(machine, button)
+                ));
+            };
+            *value += count;
+        }
+    }
+
+    match goal {
+        Goal::Lights => {
+            let lit: Vec<bool> = reached.iter().map(|&toggles| toggles % 2 == 1).collect();
+            if lit != machine.light_diagram {
+                let draw = |lights: &[bool]| -> String {
+                    lights
+                        .iter()
+                        .map(|&on| if on { '#' } else { '.' })
+                        .collect()
+                };
+                return Err(format!(
+                    "lights end up [{}] instead of [{}]",
+                    draw(&lit),
+                    draw(&machine.light_diagram)
+                ));
+            }
+        }
+        Goal::Joltages => {
+            if reached != machine.joltage_requirements {
+                return Err(format!(
+                    "joltages end up {reached:?} instead of {:?}",
+                    machine.joltage_requirements
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// How often to press each button to reach the joltage requirements with the fewest presses.
+fn find_minimum_button_presses(parsed_line: &ParsedLine) -> Option<Vec<u64>> {
+    let ParsedLine {
+        wiring_schematics,
+        joltage_requirements,
+        ..
+    } = parsed_line;
+
+    // one equation per counter, one variable per button.
+    let matrix: Vec<Vec<i64>> = (0..joltage_requirements.len() as u64)
+        .map(|counter_idx| {
+            wiring_schematics
+                .iter()
+                .map(|wiring| wiring.contains(&counter_idx) as i64)
+                .collect()
+        })
+        .collect();
+    let targets: Vec<i64> = joltage_requirements.iter().map(|&j| j as i64).collect();
+
+    // a button can't be pressed more often than the smallest counter it increments allows. A
+    // button wired to a counter without a requirement leaves the machine without a solution.
+    let upper_bounds: Vec<i64> = wiring_schematics
+        .iter()
+        .map(|wiring| {
+            let limits: Vec<i64> = wiring
+                .iter()
+                .map(|&counter_idx| targets.get(counter_idx as usize).copied())
+                .collect::<Option<_>>()?;
+            Some(limits.into_iter().min().unwrap_or(0))
+        })
+        .collect::<Option<_>>()?;
+
+    let costs = vec![1; wiring_schematics.len()];
+
+    let solution = ilp::minimize(&matrix, &targets, &costs, &upper_bounds)?;
+    Some(solution.values.iter().map(|&v| v as u64).collect())
+}
 
-    Some(result)
+/// [`part_two`] with Z3 instead of the exact solver.
+#[cfg(feature = "z3")]
+pub fn part_two_z3(input: &str) -> Option<u64> {
+    let machines = parse(input).parsed_lines;
+    machines.iter().map(find_minimum_button_presses_z3).sum()
 }
 
-fn find_minimum_button_presses_z3(parsed_line: ParsedLine) -> u64 {
+/// Reference implementation backed by Z3, used to cross-check the exact solver.
+#[cfg(feature = "z3")]
+fn find_minimum_button_presses_z3(parsed_line: &ParsedLine) -> Option<u64> {
+    use z3::ast::Int;
+    use z3::{Optimize, SatResult};
+
     let ParsedLine {
         wiring_schematics,
         joltage_requirements,
         ..
     } = parsed_line;
 
+    let counters = joltage_requirements.len() as u64;
+    if wiring_schematics
+        .iter()
+        .flatten()
+        .any(|&idx| idx >= counters)
+    {
+        return None;
+    }
+
     let optimizer = Optimize::new();
 
     let num_buttons = wiring_schematics.len();
@@ -77,128 +279,78 @@ fn find_minimum_button_presses_z3(parsed_line: ParsedLine) -> u64 {
 
     match optimizer.check(&[]) {
         SatResult::Sat => {
-            let model = optimizer.get_model().unwrap();
-            let total_value = model.eval(&total_presses, true).unwrap();
-            total_value.as_u64().unwrap()
-        }
-        SatResult::Unsat => {
-            println!(
-                "No solution found for joltage requirements: {:?}",
-                joltage_requirements
-            );
-            0
-        }
-        SatResult::Unknown => {
-            println!(
-                "Z3 could not determine satisfiability for requirements: {:?}",
-                joltage_requirements
-            );
-            0
+            let model = optimizer.get_model()?;
+            model.eval(&total_presses, true)?.as_u64()
         }
+        SatResult::Unsat | SatResult::Unknown => None,
     }
 }
 
-fn find_minimum_switches(parsed_line: ParsedLine) -> u32 {
+/// The fewest buttons that together toggle the lights into the diagram, if any do.
+fn find_minimum_switches(parsed_line: &ParsedLine) -> Option<BitVec> {
     let ParsedLine {
         light_diagram,
         wiring_schematics,
         ..
     } = parsed_line;
 
-    (0..1_u64 << wiring_schematics.len())
-        .filter_map(|bitmask| {
-            if produces_target_diagram(bitmask, &wiring_schematics, &light_diagram) {
-                Some(bitmask.count_ones())
-            } else {
-                None
-            }
+    // one equation per light, one variable per button.
+    let matrix: Vec<BitVec> = (0..light_diagram.len() as u64)
+        .map(|light_idx| {
+            let toggling = wiring_schematics
+                .iter()
+                .enumerate()
+                .filter(|(_, wiring)| wiring.contains(&light_idx))
+                .map(|(button_idx, _)| button_idx);
+            BitVec::from_indices(wiring_schematics.len(), toggling)
         })
-        .min()
-        .unwrap()
-}
-
-fn produces_target_diagram(
-    bitmask: u64,
-    wiring_schematics: &[Vec<u64>],
-    target_diagram: &[bool],
-) -> bool {
-    let mut diagram = vec![false; target_diagram.len()];
-
-    for (i, wiring_schematic) in wiring_schematics.iter().enumerate() {
-        if (bitmask & (1 << i)) != 0 {
-            toggle_lights(&mut diagram, wiring_schematic);
-        }
-    }
+        .collect();
 
-    diagram == target_diagram
-}
-
-fn toggle_lights(diagram: &mut [bool], light_indices: &[u64]) {
-    for &light_index in light_indices {
-        let index = light_index as usize;
-        diagram[index] = !diagram[index];
-    }
+    let solutions = gf2::solve(
+        &matrix,
+        &BitVec::from_bools(light_diagram),
+        wiring_schematics.len(),
+    )?;
+    Some(solutions.min_weight())
 }
 
 fn parse(input: &str) -> ParsedInput {
-    let parsed_lines = input.lines().map(parse_line).collect();
+    let parsed_lines =
+        parse_lines(input, parse_line).unwrap_or_else(|err| panic!("invalid machine: {err}"));
     ParsedInput { parsed_lines }
 }
 
-fn parse_line(line: &str) -> ParsedLine {
-    let light_diagram = parse_light_diagram(line);
-    let wiring_schematics = parse_wiring_schematics(line);
-    let joltage_requirements = parse_joltage_requirements(line);
-
-    ParsedLine {
-        light_diagram,
-        wiring_schematics,
-        joltage_requirements,
-    }
-}
-
-fn parse_light_diagram(line: &str) -> Vec<bool> {
-    let start = line.find('[').unwrap();
-    let end = line.find(']').unwrap();
-
-    line[start + 1..end]
-        .bytes()
-        .map(|byte| byte == b'#')
-        .collect()
+fn parse_machine(line: &str) -> ParsedLine {
+    parse_line(line).unwrap_or_else(|err| panic!("invalid machine: {err}"))
 }
 
-fn parse_wiring_schematics(line: &str) -> Vec<Vec<u64>> {
-    let bracket_end = line.find(']').unwrap();
-    let schematics_section = &line[bracket_end + 1..];
+fn parse_line(line: &str) -> Result<ParsedLine, ParseError> {
+    let (lights, schematics, joltages) = scan!(line, "[{}] {} {{{}}}" => &str, &str, &str)?;
 
-    schematics_section
-        .split(')')
-        .filter(|section| section.contains('('))
-        .map(parse_single_schematic)
-        .collect()
-}
+    let light_diagram = lights.bytes().map(|byte| byte == b'#').collect();
 
-fn parse_single_schematic(section: &str) -> Vec<u64> {
-    section
-        .trim_start_matches([' ', '('])
-        .split(',')
-        .map(|number_str| number_str.trim().parse().unwrap())
-        .collect()
-}
+    let wiring_schematics = schematics
+        .split_ascii_whitespace()
+        .map(|schematic| {
+            let indices = schematic.trim_start_matches('(').trim_end_matches(')');
+            comma_list(indices).map_err(|err| err.offset_by(offset_of(line, indices)))
+        })
+        .collect::<Result<_, _>>()?;
 
-fn parse_joltage_requirements(line: &str) -> Vec<u64> {
-    let start = line.find('{').unwrap();
-    let end = line.find('}').unwrap();
+    let joltage_requirements =
+        comma_list(joltages).map_err(|err| err.offset_by(offset_of(line, joltages)))?;
 
-    line[start + 1..end]
-        .split(',')
-        .map(|number_str| number_str.trim().parse().unwrap())
-        .collect()
+    Ok(ParsedLine {
+        light_diagram,
+        wiring_schematics,
+        joltage_requirements,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::generate;
 
     #[test]
     fn test_part_one() {
@@ -211,4 +363,105 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(33));
     }
+
+    #[test]
+    fn test_unreachable_joltages_have_no_answer() {
+        let input = "[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}\n[.#] (0) {1,2}\n";
+        assert_eq!(part_two(input), None);
+        // the second button increments a counter that has no requirement.
+        assert_eq!(part_two("[.#] (0) (1,2) {1,2}\n"), None);
+    }
+
+    #[test]
+    fn test_unreachable_lights_have_no_answer() {
+        let input = "[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}\n[.#] (0) {1,2}\n";
+        assert_eq!(part_one(input), None);
+        assert_eq!(part_one_line("[.#] (0) {1,2}"), None);
+    }
+
+    #[test]
+    fn test_switches_match_brute_force() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        let generated = (0..3).map(|seed| generate::input(DAY, 1.0, seed).unwrap());
+        for input in [example].into_iter().chain(generated) {
+            for machine in parse(&input).parsed_lines {
+                assert_eq!(
+                    find_minimum_switches(&machine).map(|buttons| buttons.count_ones() as u32),
+                    find_minimum_switches_brute_force(&machine)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_witnesses_are_replayed() {
+        let machine = parse_line("[.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}").unwrap();
+        assert_eq!(
+            verify_witness(&machine, &[0, 0, 0, 0, 1, 1], Goal::Lights),
+            Ok(())
+        );
+        assert_eq!(
+            verify_witness(&machine, &[0, 1, 0, 0, 0, 0], Goal::Lights),
+            Err("lights end up [.#.#] instead of [.##.]".into())
+        );
+        assert_eq!(
+            verify_witness(&machine, &[1, 3, 0, 3, 1, 2], Goal::Joltages),
+            Ok(())
+        );
+        assert!(verify_witness(&machine, &[1, 3, 0, 3, 1, 1], Goal::Joltages).is_err());
+        assert!(verify_witness(&machine, &[1], Goal::Joltages).is_err());
+    }
+
+    fn find_minimum_switches_brute_force(parsed_line: &ParsedLine) -> Option<u32> {
+        let ParsedLine {
+            light_diagram,
+            wiring_schematics,
+            ..
+        } = parsed_line;
+
+        (0..1_u64 << wiring_schematics.len())
+            .filter_map(|bitmask| {
+                if produces_target_diagram(bitmask, wiring_schematics, light_diagram) {
+                    Some(bitmask.count_ones())
+                } else {
+                    None
+                }
+            })
+            .min()
+    }
+
+    fn produces_target_diagram(
+        bitmask: u64,
+        wiring_schematics: &[Vec<u64>],
+        target_diagram: &[bool],
+    ) -> bool {
+        let mut diagram = vec![false; target_diagram.len()];
+
+        for (i, wiring_schematic) in wiring_schematics.iter().enumerate() {
+            if (bitmask & (1 << i)) != 0 {
+                toggle_lights(&mut diagram, wiring_schematic);
+            }
+        }
+
+        diagram == target_diagram
+    }
+
+    fn toggle_lights(diagram: &mut [bool], light_indices: &[u64]) {
+        for &light_index in light_indices {
+            let index = light_index as usize;
+            diagram[index] = !diagram[index];
+        }
+    }
+
+    #[cfg(feature = "z3")]
+    #[test]
+    fn test_button_presses_match_z3() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        for machine in parse(&input).parsed_lines {
+            assert_eq!(
+                find_minimum_button_presses(&machine).map(|presses| presses.iter().sum()),
+                find_minimum_button_presses_z3(&machine)
+            );
+        }
+    }
 }
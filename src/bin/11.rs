@@ -1,120 +1,141 @@
+use advent_of_code::explain::{self, Cell};
+use advent_of_code::graph::{reachable, reversed, topological_order};
 use std::collections::HashMap;
+use std::fmt::{self, Display};
 
 advent_of_code::solution!(11);
 
 struct Graph {
     labels: HashMap<String, usize>,
-    edges: HashMap<usize, Vec<usize>>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    fn label(&self, index: usize) -> &str {
+        self.labels
+            .iter()
+            .find(|&(_, &i)| i == index)
+            .map_or("?", |(label, _)| label.as_str())
+    }
+}
+
+/// The paths between two devices run through a cycle, so there are infinitely many of them.
+#[derive(Debug)]
+struct CycleError {
+    devices: Vec<String>,
+}
+
+impl Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "paths run through a cycle of {}",
+            self.devices.join(", ")
+        )
+    }
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let Graph { labels, edges } = parse(input);
-
-    let start_index = *labels.get("you").unwrap();
-    let end_index = *labels.get("out").unwrap();
-
-    let mut memo = HashMap::new();
-    let mut visited = vec![false; labels.len()];
-    let result = count_paths(
-        start_index,
-        end_index,
-        usize::MAX,
-        usize::MAX,
-        &mut visited,
-        false,
-        false,
-        &edges,
-        &mut memo,
-    );
-    Some(result)
+    let graph = parse(input);
+
+    let start_index = *graph.labels.get("you")?;
+    let end_index = *graph.labels.get("out")?;
+
+    count_paths(&graph, start_index, end_index, &[])
+        .map_err(explain_cycle)
+        .ok()
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let Graph { labels, edges } = parse(input);
-
-    let start_index = *labels.get("svr")?;
-    let end_index = *labels.get("out")?;
-    let dac_index = *labels.get("dac")?;
-    let fft_index = *labels.get("fft")?;
-
-    let mut memo = HashMap::new();
-    let mut visited = vec![false; labels.len()];
-    let result = count_paths(
-        start_index,
-        end_index,
-        dac_index,
-        fft_index,
-        &mut visited,
-        false,
-        false,
-        &edges,
-        &mut memo,
-    );
-    Some(result)
+    let graph = parse(input);
+
+    let start_index = *graph.labels.get("svr")?;
+    let end_index = *graph.labels.get("out")?;
+    let dac_index = *graph.labels.get("dac")?;
+    let fft_index = *graph.labels.get("fft")?;
+
+    count_paths(&graph, start_index, end_index, &[dac_index, fft_index])
+        .map_err(explain_cycle)
+        .ok()
 }
 
+/// Records the cycle that left a part without an answer.
+fn explain_cycle(err: CycleError) {
+    explain::row("cycles", || {
+        [("devices", Cell::from(err.devices.join(", ")))]
+    });
+}
+
+/// Counts the paths from `start` to `end` that visit every waypoint.
+///
+/// Only devices on some path from `start` to `end` matter, and those must form a DAG. Each device
+/// keeps one count per set of waypoints seen so far, as a bitmask over `waypoints`, and the counts
+/// are propagated along the topological order.
 fn count_paths(
-    current: usize,
+    graph: &Graph,
+    start: usize,
     end: usize,
-    dac: usize,
-    fft: usize,
-    visited: &mut Vec<bool>,
-    has_dac: bool,
-    has_fft: bool,
-    edges: &HashMap<usize, Vec<usize>>,
-    memo: &mut HashMap<(usize, bool, bool), u64>,
-) -> u64 {
-    if current == end {
-        return if has_dac && has_fft { 1 } else { 0 };
+    waypoints: &[usize],
+) -> Result<u64, CycleError> {
+    let from_start = reachable(&graph.edges, start);
+    let to_end = reachable(&reversed(&graph.edges), end);
+    let on_path = |node: usize| from_start[node] && to_end[node];
+
+    let adjacency: Vec<Vec<usize>> = (0..graph.edges.len())
+        .map(|node| {
+            if on_path(node) {
+                graph.edges[node]
+                    .iter()
+                    .copied()
+                    .filter(|&next| on_path(next))
+                    .collect()
+            } else {
+                vec![]
+            }
+        })
+        .collect();
+
+    let order = topological_order(&adjacency).map_err(|cycle| CycleError {
+        devices: cycle
+            .iter()
+            .map(|&node| graph.label(node).to_string())
+            .collect(),
+    })?;
+
+    let mut waypoints = waypoints.to_vec();
+    waypoints.sort_unstable();
+    waypoints.dedup();
+    let mut waypoint_bit = vec![None; order.len()];
+    for (bit, &node) in waypoints.iter().enumerate() {
+        waypoint_bit[node] = Some(1 << bit);
     }
 
-    // Check memo (but only if we haven't visited this node yet in current path)
-    if !visited[current] {
-        let key = (current, has_dac, has_fft);
-        if let Some(&cached) = memo.get(&key) {
-            return cached;
+    // counts[node * stride + seen]: paths reaching `node` having visited the waypoints in `seen`.
+    let stride = 1_usize << waypoints.len();
+    let mut counts = vec![0_u64; order.len() * stride];
+    counts[start * stride] = 1;
+
+    for &node in &order {
+        let row = node * stride;
+        if let Some(bit) = waypoint_bit[node] {
+            for seen in (0..stride).filter(|&seen| seen & bit == 0) {
+                counts[row + (seen | bit)] += std::mem::take(&mut counts[row + seen]);
+            }
         }
-    }
 
-    if visited[current] {
-        return 0;
-    }
-
-    visited[current] = true;
-
-    let new_has_dac = has_dac || (current == dac);
-    let new_has_fft = has_fft || (current == fft);
-
-    let mut total = 0;
-    if let Some(neighbors) = edges.get(&current) {
-        for &next in neighbors {
-            total += count_paths(
-                next,
-                end,
-                dac,
-                fft,
-                visited,
-                new_has_dac,
-                new_has_fft,
-                edges,
-                memo,
-            );
+        for &next in &adjacency[node] {
+            for seen in 0..stride {
+                counts[next * stride + seen] += counts[row + seen];
+            }
         }
     }
 
-    visited[current] = false;
-
-    // Only memoize if this was a "clean" call (no cycles)
-    if !visited[current] {
-        let key = (current, has_dac, has_fft);
-        memo.insert(key, total);
-    }
-
-    total
+    Ok(counts[end * stride + stride - 1])
 }
 
 fn parse(input: &str) -> Graph {
     let mut labels = HashMap::new();
+    let mut edges: Vec<Vec<usize>> = Vec::new();
 
     let mut get_or_create_index = |label: &str| {
         if let Some(&index) = labels.get(label) {
@@ -126,22 +147,26 @@ fn parse(input: &str) -> Graph {
         }
     };
 
-    let edges = input
-        .lines()
-        .map(|line| {
-            let (node, connections_str) = line.split_once(':').unwrap();
-
-            let node_index = get_or_create_index(node);
-
-            (
-                node_index,
-                connections_str
-                    .split_whitespace()
-                    .map(|connection| get_or_create_index(connection))
-                    .collect::<Vec<_>>(),
-            )
-        })
-        .collect::<HashMap<_, _>>();
+    for line in input.lines() {
+        let (node, connections_str) = line.split_once(':').unwrap();
+
+        let node_index = get_or_create_index(node);
+        let connections: Vec<usize> = connections_str
+            .split_whitespace()
+            .map(&mut get_or_create_index)
+            .collect();
+
+        let max_index = connections
+            .iter()
+            .copied()
+            .chain([node_index])
+            .max()
+            .unwrap();
+        if edges.len() <= max_index {
+            edges.resize(max_index + 1, Vec::new());
+        }
+        edges[node_index].extend(connections);
+    }
 
     Graph { labels, edges }
 }
@@ -165,4 +190,34 @@ mod tests {
         ));
         assert_eq!(result, Some(2));
     }
+
+    #[test]
+    fn test_cycles() {
+        // the cycle between aaa and bbb never leads to out, so it doesn't matter.
+        let harmless = "you: ccc aaa\naaa: bbb\nbbb: aaa\nccc: out ddd\nddd: out\n";
+        assert_eq!(part_one(harmless), Some(2));
+
+        let graph = parse("you: aaa\naaa: bbb\nbbb: aaa out\n");
+        let err = count_paths(&graph, graph.labels["you"], graph.labels["out"], &[]).unwrap_err();
+        let mut devices = err.devices;
+        devices.sort_unstable();
+        assert_eq!(devices, ["aaa", "bbb"]);
+    }
+
+    #[test]
+    fn test_waypoint_order() {
+        let graph = parse("svr: aaa bbb\naaa: bbb ccc\nbbb: ccc\nccc: out\n");
+        let index = |label: &str| graph.labels[label];
+        let count = |waypoints: &[&str]| {
+            let waypoints: Vec<usize> = waypoints.iter().map(|&w| index(w)).collect();
+            count_paths(&graph, index("svr"), index("out"), &waypoints).unwrap()
+        };
+
+        assert_eq!(count(&[]), 3);
+        assert_eq!(count(&["bbb"]), 2);
+        assert_eq!(count(&["bbb", "aaa"]), 1);
+        assert_eq!(count(&["aaa", "bbb", "ccc"]), 1);
+        assert_eq!(count(&["out", "svr"]), 3);
+        assert_eq!(count(&["ccc", "out", "bbb", "svr", "aaa"]), 1);
+    }
 }
@@ -1,11 +1,11 @@
-advent_of_code::solution!(12);
+use std::collections::HashSet;
 
-#[derive(Debug)]
-struct Shape {
-    cells: Vec<Vec<bool>>,
-    width: u32,
-    height: u32,
-}
+use advent_of_code::packing::{PlacedPiece, Polyomino, pack};
+use advent_of_code::parse::{ParseError, offset_of, parse_lines, sections, uints};
+use advent_of_code::scan;
+use advent_of_code::verify;
+
+advent_of_code::solution!(12);
 
 #[derive(Debug)]
 struct RegionRequest {
@@ -16,40 +16,133 @@ struct RegionRequest {
 
 #[derive(Debug)]
 struct ParsedInput {
-    shapes: Vec<Shape>,
+    shapes: Vec<Polyomino>,
     regions: Vec<RegionRequest>,
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
     let ParsedInput { shapes, regions } = parse(input);
 
-    let result = regions
-        .into_iter()
-        .filter(|x| can_presents_fit(x, &shapes))
+    let result = (regions.iter().enumerate())
+        .filter(|&(idx, region)| {
+            let Some(placed) = arrange_presents(region, &shapes) else {
+                return false;
+            };
+            verify::witness(format_args!("region {}", idx + 1), || {
+                verify_witness(region, &shapes, &placed)
+            });
+            true
+        })
         .count() as u64;
     Some(result)
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
+pub fn part_two(_input: &str) -> Option<u64> {
     None
 }
 
-fn can_presents_fit(region: &RegionRequest, shapes: &[Shape]) -> bool {
-    (region.width/3)*(region.height/3) >= region.quantities.iter().sum()
+/// Where every present goes, if they all fit into the region.
+fn arrange_presents(region: &RegionRequest, shapes: &[Polyomino]) -> Option<Vec<PlacedPiece>> {
+    let (width, height) = (region.width as usize, region.height as usize);
+    let counts: Vec<usize> = region.quantities.iter().map(|&q| q as usize).collect();
+
+    let Some((tile_width, tile_height)) = tile_size(region, shapes) else {
+        return pack(width, height, shapes, &counts);
+    };
+    let columns = width / tile_width;
+    let presents =
+        (counts.iter().enumerate()).flat_map(|(shape, &count)| (0..count).map(move |_| shape));
+    let placed = presents.enumerate().map(|(tile, shape)| {
+        let (left, top) = (
+            (tile % columns) * tile_width,
+            (tile / columns) * tile_height,
+        );
+        PlacedPiece {
+            shape,
+            cells: (shapes[shape].cells().iter())
+                .map(|&(x, y)| (left + x, top + y))
+                .collect(),
+        }
+    });
+    Some(placed.collect())
 }
 
+/// The size of a tile that fits any present, if the region has room for one tile per present.
+/// Presents then trivially fit without searching.
+fn tile_size(region: &RegionRequest, shapes: &[Polyomino]) -> Option<(usize, usize)> {
+    let tile_width = shapes
+        .iter()
+        .map(Polyomino::width)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let tile_height = shapes
+        .iter()
+        .map(Polyomino::height)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let tiles = (region.width as usize / tile_width) * (region.height as usize / tile_height);
+    let presents: usize = region.quantities.iter().map(|&q| q as usize).sum();
+    (tiles >= presents).then_some((tile_width, tile_height))
+}
+
+/// Checks that the placed presents are the requested ones, in bounds and not overlapping.
+fn verify_witness(
+    region: &RegionRequest,
+    shapes: &[Polyomino],
+    placed: &[PlacedPiece],
+) -> Result<(), String> {
+    let mut counts = vec![0; shapes.len()];
+    let mut covered = HashSet::new();
+    for piece in placed {
+        let Some(shape) = shapes.get(piece.shape) else {
+            return Err(format!("placed unknown shape {}", piece.shape));
+        };
+        counts[piece.shape] += 1;
+
+        let cells = Polyomino::from_cells(piece.cells.iter().copied());
+        if !shape.orientations().contains(&cells) {
+            return Err(format!("shape {} placed as {:?}", piece.shape, piece.cells));
+        }
+        for &(x, y) in &piece.cells {
+            if x >= region.width as usize || y >= region.height as usize {
+                return Err(format!(
+                    "shape {} placed out of bounds at {x},{y}",
+                    piece.shape
+                ));
+            }
+            if !covered.insert((x, y)) {
+                return Err(format!(
+                    "shape {} overlaps another present at {x},{y}",
+                    piece.shape
+                ));
+            }
+        }
+    }
+
+    let requested =
+        (0..shapes.len()).map(|idx| region.quantities.get(idx).map_or(0, |&q| q as usize));
+    if !requested.eq(counts.iter().copied()) {
+        return Err(format!(
+            "placed {counts:?} presents instead of {:?}",
+            region.quantities
+        ));
+    }
+    Ok(())
+}
 
 fn parse(input: &str) -> ParsedInput {
-    let mut shapes = input.split("\n\n").collect::<Vec<_>>();
+    let mut shapes = sections(input).collect::<Vec<_>>();
     let regions = shapes.pop().unwrap();
+    let shapes = parse_shapes(shapes);
+    let regions =
+        parse_regions(regions, shapes.len()).unwrap_or_else(|err| panic!("invalid region: {err}"));
 
-    ParsedInput {
-        shapes: parse_shapes(shapes),
-        regions: parse_regions(regions),
-    }
+    ParsedInput { shapes, regions }
 }
 
-fn parse_shapes(shapes_str: Vec<&str>) -> Vec<Shape> {
+fn parse_shapes(shapes_str: Vec<&str>) -> Vec<Polyomino> {
     shapes_str
         .into_iter()
         .map(|shape| {
@@ -58,36 +151,31 @@ fn parse_shapes(shapes_str: Vec<&str>) -> Vec<Shape> {
                 .skip(1)
                 .map(|line| line.bytes().map(|b| b == b'#').collect::<Vec<_>>())
                 .collect::<Vec<_>>();
-            Shape {
-                width: cells.len() as u32,
-                height: cells.first().unwrap().len() as u32,
-                cells,
-            }
+            Polyomino::from_grid(&cells)
         })
         .collect::<Vec<_>>()
 }
 
-fn parse_regions(regions_str: &str) -> Vec<RegionRequest> {
-    regions_str
-        .lines()
-        .map(|line| {
-            let (region, quantities) = line.split_once(": ").unwrap();
-            let (width, height) = region.split_once('x').unwrap();
-
-            let width = width.parse::<u32>().unwrap();
-            let height = height.parse::<u32>().unwrap();
-            let quantities = quantities
-                .split(" ")
-                .map(|x| x.parse::<u32>().unwrap())
-                .collect::<Vec<_>>();
-
-            RegionRequest {
-                width,
-                height,
-                quantities,
-            }
+/// Regions with one quantity for each of the `num_shapes` shapes.
+fn parse_regions(regions_str: &str, num_shapes: usize) -> Result<Vec<RegionRequest>, ParseError> {
+    parse_lines(regions_str, |line| {
+        let (width, height, quantities_str) = scan!(line, "{}x{}: {}" => u32, u32, &str)?;
+        let offset = offset_of(line, quantities_str);
+        let quantities = uints::<u32>(quantities_str).map_err(|err| err.offset_by(offset))?;
+        if quantities.len() != num_shapes {
+            let message = format!(
+                "expected {num_shapes} quantities, found {}",
+                quantities.len()
+            );
+            return Err(ParseError::new(1, message).offset_by(offset));
+        }
+
+        Ok(RegionRequest {
+            width,
+            height,
+            quantities,
         })
-        .collect::<Vec<_>>()
+    })
 }
 
 #[cfg(test)]
@@ -105,4 +193,35 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, None);
     }
+
+    #[test]
+    fn test_witnesses_are_checked() {
+        let ParsedInput { shapes, regions } =
+            parse(&advent_of_code::template::read_file("examples", DAY));
+        let region = &regions[0];
+        let mut placed = arrange_presents(region, &shapes).unwrap();
+        assert_eq!(verify_witness(region, &shapes, &placed), Ok(()));
+
+        // a present outside the region and two presents on top of each other.
+        let shifted = |piece: &PlacedPiece, dx: usize| PlacedPiece {
+            shape: piece.shape,
+            cells: piece.cells.iter().map(|&(x, y)| (x + dx, y)).collect(),
+        };
+        let last = placed.pop().unwrap();
+        let outside = shifted(&last, region.width as usize);
+        assert!(
+            verify_witness(region, &shapes, &[placed.clone(), vec![outside]].concat()).is_err()
+        );
+        let twice = [placed.clone(), vec![placed[0].clone()]].concat();
+        assert!(verify_witness(region, &shapes, &twice).is_err());
+        assert!(verify_witness(region, &shapes, &placed).is_err());
+    }
+
+    #[test]
+    fn test_rejects_regions_with_wrong_quantity_count() {
+        let err = parse_regions("4x4: 0 0 0 0 0 2\n12x5: 1 0 1 0 2\n", 6).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 7));
+        assert!(err.message.contains("expected 6 quantities, found 5"));
+        assert!(parse_regions("4x4: 0 0\n", 1).is_err());
+    }
 }
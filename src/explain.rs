@@ -0,0 +1,247 @@
+//! Witness data that explains how an answer was reached.
+//!
+//! Parts add rows to named tables through [`row`], e.g. one row per input line with what was
+//! chosen for it, or a single row describing the winning object. Rows are closures and only
+//! evaluated once the runner turned collection on with [`enable`], which it does for `--explain`
+//! and `--json`, so explaining costs nothing otherwise. The runner collects the tables of every
+//! part with [`take`] and prints them with [`render`] or as JSON with [`to_json`].
+use std::collections::HashMap;
+use std::fmt::{self, Display, Write as _};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tinyjson::JsonValue;
+
+use crate::template::{ANSI_BOLD, ANSI_RESET};
+
+/// Integers beyond this can't be represented exactly by a JSON number.
+const MAX_SAFE_INTEGER: i128 = (1 << 53) - 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cell {
+    Int(i128),
+    Text(String),
+}
+
+macro_rules! cell_from_int {
+    ($($int:ty),*) => {
+        $(impl From<$int> for Cell {
+            fn from(value: $int) -> Self {
+                Cell::Int(value as i128)
+            }
+        })*
+    };
+}
+
+cell_from_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, i128);
+
+impl From<&str> for Cell {
+    fn from(value: &str) -> Self {
+        Cell::Text(value.into())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(value: String) -> Self {
+        Cell::Text(value)
+    }
+}
+
+impl Display for Cell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cell::Int(value) => write!(f, "{value}"),
+            Cell::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+impl From<&Cell> for JsonValue {
+    fn from(cell: &Cell) -> Self {
+        match cell {
+            Cell::Int(value) if value.unsigned_abs() <= MAX_SAFE_INTEGER as u128 => {
+                JsonValue::Number(*value as f64)
+            }
+            cell => JsonValue::String(cell.to_string()),
+        }
+    }
+}
+
+/// Rows sharing a name. Columns appear in the order they were first used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    pub name: String,
+    pub columns: Vec<String>,
+    /// One cell per column, `None` where a row didn't set the column.
+    pub rows: Vec<Vec<Option<Cell>>>,
+}
+
+impl Table {
+    fn push(&mut self, row: impl IntoIterator<Item = (&'static str, Cell)>) {
+        let known_columns = self.columns.len();
+        let mut cells = vec![None; self.columns.len()];
+        for (column, cell) in row {
+            let idx = match self.columns.iter().position(|c| c == column) {
+                Some(idx) => idx,
+                None => {
+                    self.columns.push(column.into());
+                    cells.push(None);
+                    self.columns.len() - 1
+                }
+            };
+            cells[idx] = Some(cell);
+        }
+        self.rows.push(cells);
+        // earlier rows don't have the columns this one introduced.
+        if self.columns.len() > known_columns {
+            for row in &mut self.rows {
+                row.resize(self.columns.len(), None);
+            }
+        }
+    }
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static TABLES: Mutex<Vec<Table>> = Mutex::new(Vec::new());
+
+/// Starts or stops collecting rows.
+pub fn enable(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether rows are being collected.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Adds the row produced by `row` to the table called `table`.
+pub fn row<R>(table: &str, row: impl FnOnce() -> R)
+where
+    R: IntoIterator<Item = (&'static str, Cell)>,
+{
+    if !enabled() {
+        return;
+    }
+    let mut tables = TABLES.lock().unwrap_or_else(|err| err.into_inner());
+    let idx = match tables.iter().position(|t| t.name == table) {
+        Some(idx) => idx,
+        None => {
+            tables.push(Table {
+                name: table.into(),
+                columns: vec![],
+                rows: vec![],
+            });
+            tables.len() - 1
+        }
+    };
+    tables[idx].push(row());
+}
+
+/// Removes and returns the tables collected so far.
+pub fn take() -> Vec<Table> {
+    std::mem::take(&mut *TABLES.lock().unwrap_or_else(|err| err.into_inner()))
+}
+
+/// Tables as aligned text, numbers aligned to the right.
+pub fn render(tables: &[Table]) -> String {
+    let mut out = String::new();
+    for table in tables {
+        let text = |cell: &Option<Cell>| cell.as_ref().map(Cell::to_string).unwrap_or_default();
+        let widths: Vec<usize> = (0..table.columns.len())
+            .map(|idx| {
+                let cells = table.rows.iter().map(|row| text(&row[idx]).chars().count());
+                cells
+                    .chain([table.columns[idx].chars().count()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let _ = writeln!(out, "{ANSI_BOLD}{}{ANSI_RESET}", table.name);
+        let header: Vec<String> = (table.columns.iter().zip(&widths))
+            .map(|(column, &width)| format!("{column:<width$}"))
+            .collect();
+        let _ = writeln!(out, "  {}", header.join("  ").trim_end());
+        let rule: Vec<String> = widths.iter().map(|&width| "─".repeat(width)).collect();
+        let _ = writeln!(out, "  {}", rule.join("  "));
+
+        for row in &table.rows {
+            let cells: Vec<String> = (row.iter().zip(&widths))
+                .map(|(cell, &width)| match cell {
+                    Some(Cell::Int(value)) => format!("{value:>width$}"),
+                    cell => format!("{:<width$}", text(cell)),
+                })
+                .collect();
+            let _ = writeln!(out, "  {}", cells.join("  ").trim_end());
+        }
+    }
+    out
+}
+
+/// Tables as a JSON object with one array of row objects per table.
+pub fn to_json(tables: &[Table]) -> JsonValue {
+    let tables = tables.iter().map(|table| {
+        let rows = table.rows.iter().map(|row| {
+            let cells = (table.columns.iter().zip(row)).filter_map(|(column, cell)| {
+                Some((column.clone(), JsonValue::from(cell.as_ref()?)))
+            });
+            JsonValue::Object(cells.collect())
+        });
+        (table.name.clone(), JsonValue::Array(rows.collect()))
+    });
+    JsonValue::Object(tables.collect::<HashMap<_, _>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Table {
+        let mut table = Table {
+            name: "machines".into(),
+            columns: vec![],
+            rows: vec![],
+        };
+        table.push([("machine", 1.into()), ("buttons", "(0,2)".into())]);
+        table.push([("machine", 10.into()), ("presses", Cell::from(1_u64 << 60))]);
+        table
+    }
+
+    #[test]
+    fn collects_columns_in_order() {
+        let table = sample();
+        assert_eq!(table.columns, ["machine", "buttons", "presses"]);
+        assert_eq!(table.rows[0][2], None);
+        assert_eq!(table.rows[1][1], None);
+    }
+
+    #[test]
+    fn renders_tables() {
+        let text = render(&[sample()]);
+        let lines: Vec<&str> = text.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            [
+                "  machine  buttons  presses",
+                "  ───────  ───────  ───────────────────",
+                "        1  (0,2)",
+                "       10           1152921504606846976",
+            ]
+        );
+
+        let json = to_json(&[sample()]).stringify().unwrap();
+        let parsed: JsonValue = json.parse().unwrap();
+        let rows: &Vec<JsonValue> = parsed["machines"].get().unwrap();
+        assert_eq!(rows[0]["machine"], JsonValue::Number(1.0));
+        assert_eq!(rows[0]["buttons"], JsonValue::String("(0,2)".into()));
+        // too large for a JSON number.
+        assert_eq!(
+            rows[1]["presses"],
+            JsonValue::String("1152921504606846976".into())
+        );
+        assert_eq!(
+            JsonValue::from(&Cell::from(i128::MIN)),
+            JsonValue::String(i128::MIN.to_string())
+        );
+    }
+}
@@ -0,0 +1,635 @@
+//! Seeded generators for puzzle inputs in the real format, at any scale.
+//!
+//! The examples and our own inputs are too small to show how a solution scales. Every generator
+//! produces an input shaped like the real one, where `size` 1 is about as large as a real input and
+//! other sizes scale the amount of work, usually the number of lines. Sizes below 1 give small
+//! inputs for tests. The same day, size and seed always produce the same input, so a slow or failing
+//! input can be recreated from its command line.
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
+
+use crate::geom::Point2;
+use crate::template::Day;
+
+/// The input for `day` scaled by `size`, or `None` if there's no generator for the day.
+pub fn input(day: Day, size: f64, seed: u64) -> Option<String> {
+    assert!(size > 0.0, "size must be positive, got {size}");
+    let mut rng = Rng::new(seed ^ u64::from(day.into_inner()) << 56);
+    let input = match day.into_inner() {
+        1 => rotations(&mut rng, size),
+        2 => id_ranges(&mut rng, size),
+        3 => battery_banks(&mut rng, size),
+        4 => paper_rolls(&mut rng, size),
+        5 => ingredients(&mut rng, size),
+        6 => worksheet(&mut rng, size),
+        7 => manifold(&mut rng, size),
+        8 => junction_boxes(&mut rng, size),
+        9 => red_tiles(&mut rng, size),
+        10 => machines(&mut rng, size),
+        11 => devices(&mut rng, size),
+        12 => presents(&mut rng, size),
+        _ => return None,
+    };
+    Some(input)
+}
+
+/// A xorshift generator. Not suitable for anything but reproducible test data.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // scramble the seed with splitmix64, so that similar seeds give unrelated sequences and a
+        // seed of 0 doesn't get stuck.
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Self {
+            state: (z ^ (z >> 31)).max(1),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// A number in `0..bound`. `bound` must not be 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// A number in `low..=high`.
+    pub fn between(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next_u64() % (high - low + 1)
+    }
+
+    /// An index into a collection of `len` items.
+    pub fn index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    /// `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64 <= p
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.index(idx + 1));
+        }
+    }
+
+    /// `count` distinct numbers in `1..=max`, sorted.
+    fn distinct(&mut self, count: usize, max: u64) -> Vec<u64> {
+        assert!(
+            count as u64 <= max,
+            "can't pick {count} distinct numbers up to {max}"
+        );
+        let mut picked = BTreeSet::new();
+        while picked.len() < count {
+            picked.insert(self.between(1, max));
+        }
+        picked.into_iter().collect()
+    }
+}
+
+/// `base` scaled by `size`, but at least 1.
+fn scaled(base: usize, size: f64) -> usize {
+    ((base as f64 * size).round() as usize).max(1)
+}
+
+/// Side length of a square grid whose area grows with `size`.
+fn side(base: usize, size: f64) -> usize {
+    scaled(base, size.sqrt())
+}
+
+/// Day 01: dial rotations.
+fn rotations(rng: &mut Rng, size: f64) -> String {
+    (0..scaled(4500, size))
+        .map(|_| {
+            let direction = if rng.chance(0.5) { 'L' } else { 'R' };
+            format!("{direction}{}\n", rng.between(1, 999))
+        })
+        .collect()
+}
+
+/// Day 02: comma-separated ID ranges. Ranges get wider and their IDs longer with `size`.
+fn id_ranges(rng: &mut Rng, size: f64) -> String {
+    let max_digits = (10 + size.log10().max(0.0) as u32).min(18);
+    let max_width = scaled(100_000, size) as u64;
+    let mut starts: Vec<u64> = (0..scaled(35, size))
+        .map(|_| {
+            let digits = rng.between(1, u64::from(max_digits)) as u32;
+            rng.between(10_u64.pow(digits - 1), 10_u64.pow(digits) - 1)
+        })
+        .collect();
+    starts.sort_unstable();
+    starts.dedup();
+
+    // ranges don't overlap, like in the real input.
+    let mut ranges: Vec<String> = (0..starts.len())
+        .map(|idx| {
+            let next = starts.get(idx + 1).map_or(u64::MAX, |&next| next - 1);
+            let end = (starts[idx] + rng.below(max_width)).min(next);
+            format!("{}-{end}", starts[idx])
+        })
+        .collect();
+    rng.shuffle(&mut ranges);
+    ranges.join(",") + "\n"
+}
+
+/// Day 03: banks of battery joltages.
+fn battery_banks(rng: &mut Rng, size: f64) -> String {
+    (0..scaled(200, size))
+        .map(|_| {
+            let bank: String = (0..100)
+                .map(|_| char::from(b'1' + rng.below(9) as u8))
+                .collect();
+            bank + "\n"
+        })
+        .collect()
+}
+
+/// Day 04: a square map of paper rolls.
+fn paper_rolls(rng: &mut Rng, size: f64) -> String {
+    let side = side(137, size);
+    (0..side)
+        .map(|_| {
+            let row: String = (0..side)
+                .map(|_| if rng.chance(0.65) { '@' } else { '.' })
+                .collect();
+            row + "\n"
+        })
+        .collect()
+}
+
+/// Day 05: overlapping ranges of fresh ingredient IDs, then the available IDs.
+fn ingredients(rng: &mut Rng, size: f64) -> String {
+    const MAX_ID: u64 = 560_000_000_000_000;
+    let mut out = String::new();
+    for _ in 0..scaled(190, size) {
+        let start = rng.between(1, MAX_ID);
+        let end = start + rng.below(MAX_ID / 100);
+        let _ = writeln!(out, "{start}-{end}");
+    }
+    out.push('\n');
+    for _ in 0..scaled(1000, size) {
+        let _ = writeln!(out, "{}", rng.between(1, MAX_ID));
+    }
+    out
+}
+
+/// Day 06: a worksheet of problems written in columns, with the operator below each problem.
+fn worksheet(rng: &mut Rng, size: f64) -> String {
+    let mut rows = vec![String::new(); 5];
+    for problem in 0..scaled(1000, size) {
+        let numbers: Vec<String> = (0..4)
+            .map(|_| {
+                let digits = rng.between(1, 4) as u32;
+                rng.between(10_u64.pow(digits - 1), 10_u64.pow(digits) - 1)
+                    .to_string()
+            })
+            .collect();
+        let width = numbers.iter().map(String::len).max().unwrap_or(1);
+        let right_aligned = rng.chance(0.5);
+        let operator = if rng.chance(0.5) { "+" } else { "*" };
+
+        for (row, number) in rows.iter_mut().zip(&numbers) {
+            if problem > 0 {
+                row.push(' ');
+            }
+            let _ = if right_aligned {
+                write!(row, "{number:>width$}")
+            } else {
+                write!(row, "{number:<width$}")
+            };
+        }
+        if problem > 0 {
+            rows[4].push(' ');
+        }
+        let _ = write!(rows[4], "{operator:<width$}");
+    }
+    rows.iter().map(|row| format!("{row}\n")).collect()
+}
+
+/// Day 07: a tachyon manifold with splitters in a widening cone below the start.
+fn manifold(rng: &mut Rng, size: f64) -> String {
+    manifold_of_width(rng, side(141, size) | 1)
+}
+
+/// A day 07 manifold `width` columns wide, including even widths the generator never picks.
+pub fn manifold_of_width(rng: &mut Rng, width: usize) -> String {
+    let centre = width / 2;
+    let mut rows = vec![vec!['.'; width]; width + 1];
+    rows[0][centre] = 'S';
+    for level in 1..=width / 2 {
+        let row = &mut rows[2 * level];
+        for x in (centre + 1 - level..=centre + level - 1).step_by(2) {
+            if rng.chance(0.7) {
+                row[x] = '^';
+            }
+        }
+    }
+    rows.iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
+}
+
+/// Day 08: junction boxes in a cube.
+fn junction_boxes(rng: &mut Rng, size: f64) -> String {
+    (0..scaled(1000, size))
+        .map(|_| {
+            let [x, y, z] = [(); 3].map(|_| rng.below(100_000));
+            format!("{x},{y},{z}\n")
+        })
+        .collect()
+}
+
+/// Day 09: the red corner tiles of a simple rectilinear polygon, in order around it.
+///
+/// The polygon is a staircase through every quadrant around its centre. Anchor points move away
+/// from one axis and towards the next one, so every quadrant's staircase is monotone and never
+/// crosses another. No two anchors share a coordinate, so every tile is a proper corner.
+fn red_tiles(rng: &mut Rng, size: f64) -> String {
+    let per_quadrant = scaled(62, size);
+    let radius = scaled(48_000, size).max(4 * per_quadrant) as u64;
+    let centre = radius + 1_000;
+
+    let offsets = |rng: &mut Rng| {
+        let mut offsets = rng.distinct(4 * per_quadrant, radius);
+        rng.shuffle(&mut offsets);
+        let mut quadrants: Vec<Vec<u64>> =
+            offsets.chunks(per_quadrant).map(<[u64]>::to_vec).collect();
+        quadrants
+            .iter_mut()
+            .for_each(|quadrant| quadrant.sort_unstable());
+        quadrants
+    };
+    let (dx, dy) = (offsets(rng), offsets(rng));
+
+    // counterclockwise from the east: every quadrant walks from the axis it leaves to the next one.
+    let mut anchors = vec![];
+    let quadrants = [(true, true), (false, true), (false, false), (true, false)];
+    for (quadrant, (east, north)) in quadrants.into_iter().enumerate() {
+        let (mut xs, mut ys) = (dx[quadrant].clone(), dy[quadrant].clone());
+        if east == north {
+            xs.reverse();
+        } else {
+            ys.reverse();
+        }
+        for (x, y) in xs.into_iter().zip(ys) {
+            let x = if east { centre + x } else { centre - x };
+            let y = if north { centre + y } else { centre - y };
+            anchors.push((x, y));
+        }
+    }
+
+    let mut out = String::new();
+    for (idx, &(x, y)) in anchors.iter().enumerate() {
+        let (next_x, _) = anchors[(idx + 1) % anchors.len()];
+        let _ = writeln!(out, "{x},{y}\n{next_x},{y}");
+    }
+    out
+}
+
+/// Day 10: machines whose light diagrams and joltages are reachable by construction. Machines get
+/// more lights and buttons with `size`, not just more lines.
+fn machines(rng: &mut Rng, size: f64) -> String {
+    let max_lights = 10 + size.log2().max(0.0) as u64;
+    let mut out = String::new();
+    for _ in 0..scaled(170, size) {
+        let lights = rng.between(3, max_lights) as usize;
+        let num_buttons = rng.between(lights as u64 - 1, lights as u64 + 3) as usize;
+        let mut buttons: Vec<Vec<usize>> = (0..num_buttons)
+            .map(|_| {
+                let wired = rng.between(1, lights as u64 - 1) as usize;
+                let mut counters: Vec<usize> = (0..lights).collect();
+                rng.shuffle(&mut counters);
+                counters.truncate(wired);
+                counters
+            })
+            .collect();
+        // every counter is wired to some button.
+        for counter in 0..lights {
+            if !buttons.iter().any(|button| button.contains(&counter)) {
+                let idx = rng.index(num_buttons);
+                buttons[idx].push(counter);
+            }
+        }
+        buttons.iter_mut().for_each(|button| button.sort_unstable());
+
+        let mut diagram = vec![false; lights];
+        let mut joltages = vec![0; lights];
+        for button in &buttons {
+            let toggled = rng.chance(0.5);
+            let presses = rng.below(21);
+            for &counter in button {
+                diagram[counter] ^= toggled;
+                joltages[counter] += presses;
+            }
+        }
+
+        let diagram: String = diagram
+            .iter()
+            .map(|&on| if on { '#' } else { '.' })
+            .collect();
+        let buttons: Vec<String> = buttons
+            .iter()
+            .map(|button| {
+                let counters: Vec<String> = button.iter().map(usize::to_string).collect();
+                format!("({})", counters.join(","))
+            })
+            .collect();
+        let joltages: Vec<String> = joltages.iter().map(u64::to_string).collect();
+        let _ = writeln!(
+            out,
+            "[{diagram}] {} {{{}}}",
+            buttons.join(" "),
+            joltages.join(",")
+        );
+    }
+    out
+}
+
+/// Day 11: a layered network of devices. Every device is reachable from `svr` and leads to `out`,
+/// and `you`, `fft` and `dac` sit in early, middle and late layers.
+fn devices(rng: &mut Rng, size: f64) -> String {
+    const LAYERS: usize = 24;
+    const RESERVED: [&str; 5] = ["svr", "you", "fft", "dac", "out"];
+
+    let width = scaled(25, size);
+    let name_length = if LAYERS * width + RESERVED.len() < 26 * 26 * 26 / 2 {
+        3
+    } else {
+        4
+    };
+    let mut names = BTreeSet::new();
+    let mut name = |rng: &mut Rng| loop {
+        let name: String = (0..name_length)
+            .map(|_| char::from(b'a' + rng.below(26) as u8))
+            .collect();
+        if !RESERVED.contains(&name.as_str()) && names.insert(name.clone()) {
+            return name;
+        }
+    };
+
+    let mut layers: Vec<Vec<String>> = vec![vec!["svr".into()]];
+    for layer in 1..LAYERS {
+        let mut devices: Vec<String> = (0..width).map(|_| name(rng)).collect();
+        match layer {
+            4 => devices[0] = "you".into(),
+            9 => devices[0] = "fft".into(),
+            17 => devices[0] = "dac".into(),
+            _ => {}
+        }
+        rng.shuffle(&mut devices);
+        layers.push(devices);
+    }
+
+    let mut outputs: Vec<Vec<Vec<&str>>> = layers
+        .iter()
+        .map(|layer| vec![vec![]; layer.len()])
+        .collect();
+    for layer in 0..LAYERS - 1 {
+        let next = &layers[layer + 1];
+        let mut reached = vec![false; next.len()];
+        for outputs in &mut outputs[layer] {
+            for _ in 0..rng.between(1, 3) {
+                let target = rng.index(next.len());
+                if !outputs.contains(&next[target].as_str()) {
+                    outputs.push(&next[target]);
+                    reached[target] = true;
+                }
+            }
+        }
+        // every device has an input.
+        for (target, _) in reached.iter().enumerate().filter(|&(_, &reached)| !reached) {
+            let source = rng.index(layers[layer].len());
+            outputs[layer][source].push(&next[target]);
+        }
+    }
+    for outputs in &mut outputs[LAYERS - 1] {
+        outputs.push("out");
+    }
+
+    let mut lines: Vec<String> = (layers.iter().zip(&outputs))
+        .flat_map(|(layer, outputs)| layer.iter().zip(outputs))
+        .map(|(device, outputs)| format!("{device}: {}\n", outputs.join(" ")))
+        .collect();
+    rng.shuffle(&mut lines);
+    lines.concat()
+}
+
+/// Day 12: six present shapes, then regions that either trivially fit their presents or can't
+/// fit them by area alone, like in the real input.
+fn presents(rng: &mut Rng, size: f64) -> String {
+    let shapes: Vec<[[bool; 3]; 3]> = (0..6).map(|_| present(rng)).collect();
+    let areas: Vec<u64> = shapes
+        .iter()
+        .map(|shape| shape.iter().flatten().filter(|&&cell| cell).count() as u64)
+        .collect();
+
+    let mut out = String::new();
+    for (idx, shape) in shapes.iter().enumerate() {
+        let _ = writeln!(out, "{idx}:");
+        for row in shape {
+            let _ = writeln!(
+                out,
+                "{}",
+                row.map(|cell| if cell { '#' } else { '.' })
+                    .iter()
+                    .collect::<String>()
+            );
+        }
+        out.push('\n');
+    }
+
+    for _ in 0..scaled(1000, size) {
+        let (width, height) = (rng.between(35, 50), rng.between(35, 50));
+        let mut quantities = vec![0; shapes.len()];
+        if rng.chance(0.5) {
+            // one 3x3 tile per present.
+            let tiles = (width / 3) * (height / 3);
+            for _ in 0..tiles - rng.below(tiles / 4) {
+                quantities[rng.index(shapes.len())] += 1;
+            }
+        } else {
+            let mut area = 0;
+            while area <= width * height {
+                let shape = rng.index(shapes.len());
+                quantities[shape] += 1;
+                area += areas[shape];
+            }
+        }
+        let quantities: Vec<String> = quantities.iter().map(u64::to_string).collect();
+        let _ = writeln!(out, "{width}x{height}: {}", quantities.join(" "));
+    }
+    out
+}
+
+/// A connected present of 5 to 7 cells that spans its whole 3x3 box.
+fn present(rng: &mut Rng) -> [[bool; 3]; 3] {
+    loop {
+        let mut cells = [[false; 3]; 3];
+        cells[1][1] = true;
+        let target = rng.between(5, 7);
+        let mut count = 1;
+        while count < target {
+            let (x, y) = (rng.index(3), rng.index(3));
+            let neighbours = [(0, 1), (2, 1), (1, 0), (1, 2)];
+            let touches = neighbours.iter().any(|&(dx, dy)| {
+                let (nx, ny) = ((x + dx).wrapping_sub(1), (y + dy).wrapping_sub(1));
+                nx < 3 && ny < 3 && cells[ny][nx]
+            });
+            if !cells[y][x] && touches {
+                cells[y][x] = true;
+                count += 1;
+            }
+        }
+        let spans_rows = cells.iter().all(|row| row.contains(&true));
+        let spans_columns = (0..3).all(|x| cells.iter().any(|row| row[x]));
+        if spans_rows && spans_columns {
+            return cells;
+        }
+    }
+}
+
+/// A random simple rectilinear polygon: the outline of a polyomino grown cell by cell on a
+/// small grid, with grid lines spaced one to three tiles apart so that edges can run right
+/// next to each other. Polyominoes with holes or cells touching only at a corner are redrawn,
+/// as their outline is not a simple polygon.
+pub fn rectilinear_polygon(rng: &mut Rng) -> Vec<Point2<i64>> {
+    const N: i64 = 6;
+    loop {
+        let mut cells = HashSet::from([(rng.below(N as u64) as i64, rng.below(N as u64) as i64)]);
+        for _ in 0..rng.between(10, 120) {
+            let cell = (rng.below(N as u64) as i64, rng.below(N as u64) as i64);
+            let (x, y) = cell;
+            if [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                .iter()
+                .any(|neighbour| cells.contains(neighbour))
+            {
+                cells.insert(cell);
+            }
+        }
+        let filled = |x: i64, y: i64| cells.contains(&(x, y));
+
+        let pinched = (-1..N).any(|x| {
+            (-1..N).any(|y| {
+                filled(x, y) == filled(x + 1, y + 1)
+                    && filled(x + 1, y) == filled(x, y + 1)
+                    && filled(x, y) != filled(x + 1, y)
+            })
+        });
+        let mut outside = HashSet::from([(-1, -1)]);
+        let mut stack = vec![(-1, -1)];
+        while let Some((x, y)) = stack.pop() {
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                let in_bounds = (-1..=N).contains(&nx) && (-1..=N).contains(&ny);
+                if in_bounds && !filled(nx, ny) && outside.insert((nx, ny)) {
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        let holes = (N + 2) * (N + 2) != (outside.len() + cells.len()) as i64;
+        if pinched || holes {
+            continue;
+        }
+
+        // unit edges of the outline, counterclockwise, keyed by their start.
+        let mut next = HashMap::new();
+        for &(x, y) in &cells {
+            if !filled(x, y - 1) {
+                next.insert((x, y), (x + 1, y));
+            }
+            if !filled(x + 1, y) {
+                next.insert((x + 1, y), (x + 1, y + 1));
+            }
+            if !filled(x, y + 1) {
+                next.insert((x + 1, y + 1), (x, y + 1));
+            }
+            if !filled(x - 1, y) {
+                next.insert((x, y + 1), (x, y));
+            }
+        }
+
+        let lines = |rng: &mut Rng| {
+            let mut lines = vec![0];
+            for _ in 0..=N {
+                lines.push(lines.last().unwrap() + rng.between(1, 3) as i64);
+            }
+            lines
+        };
+        let (xs, ys) = (lines(rng), lines(rng));
+
+        let start = *next.keys().min().unwrap();
+        let mut vertices = vec![];
+        let (mut previous, mut current) = (start, next[&start]);
+        loop {
+            let following = next[&current];
+            let turns = (current.0 - previous.0, current.1 - previous.1)
+                != (following.0 - current.0, following.1 - current.1);
+            if turns {
+                vertices.push(Point2::new(xs[current.0 as usize], ys[current.1 as usize]));
+            }
+            if current == start {
+                return vertices;
+            }
+            (previous, current) = (current, following);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day;
+
+    #[test]
+    fn is_deterministic() {
+        for day in crate::template::all_days() {
+            let Some(generated) = input(day, 1.0, 7) else {
+                continue;
+            };
+            assert_eq!(Some(&generated), input(day, 1.0, 7).as_ref(), "day {day}");
+            assert_ne!(Some(&generated), input(day, 1.0, 8).as_ref(), "day {day}");
+        }
+        assert_eq!(input(day!(25), 1.0, 7), None);
+    }
+
+    #[test]
+    fn shrinks_below_size_one() {
+        for day in crate::template::all_days() {
+            let (Some(small), Some(full)) = (input(day, 0.01, 7), input(day, 1.0, 7)) else {
+                continue;
+            };
+            assert!(!small.trim().is_empty(), "day {day}");
+            assert!(small.len() < full.len(), "day {day}");
+        }
+    }
+
+    #[test]
+    fn draws_rectilinear_polygons() {
+        let points: Vec<(u64, u64)> = input(day!(9), 2.0, 3)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let (x, y) = line.split_once(',').unwrap();
+                (x.parse().unwrap(), y.parse().unwrap())
+            })
+            .collect();
+        assert_eq!(points.len(), 8 * 62 * 2);
+        assert_eq!(points.iter().collect::<BTreeSet<_>>().len(), points.len());
+        for (idx, &(x, y)) in points.iter().enumerate() {
+            let (next_x, next_y) = points[(idx + 1) % points.len()];
+            // consecutive tiles share exactly one coordinate, alternating between the two.
+            assert_eq!(x == next_x, idx % 2 == 1, "{idx}");
+            assert_eq!(y == next_y, idx % 2 == 0, "{idx}");
+        }
+    }
+}
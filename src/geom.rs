@@ -0,0 +1,570 @@
+//! Points, coordinate compression and (rectilinear) polygon geometry.
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use crate::num::{Int, gcd};
+
+/// A point (or vector) in two dimensions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// A point (or vector) in three dimensions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+}
+
+#[inline]
+fn abs_diff<T: Int>(a: T, b: T) -> T {
+    if a > b { a - b } else { b - a }
+}
+
+impl<T: Int> Point2<T> {
+    /// Manhattan distance, safe for unsigned coordinates.
+    pub fn manhattan(self, other: Self) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y)
+    }
+
+    /// Squared euclidean distance, safe for unsigned coordinates.
+    pub fn distance_squared(self, other: Self) -> T {
+        let (dx, dy) = (abs_diff(self.x, other.x), abs_diff(self.y, other.y));
+        dx * dx + dy * dy
+    }
+}
+
+impl<T: Int> Point3<T> {
+    /// Manhattan distance, safe for unsigned coordinates.
+    pub fn manhattan(self, other: Self) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y) + abs_diff(self.z, other.z)
+    }
+
+    /// Squared euclidean distance, safe for unsigned coordinates.
+    pub fn distance_squared(self, other: Self) -> T {
+        let (dx, dy, dz) = (
+            abs_diff(self.x, other.x),
+            abs_diff(self.y, other.y),
+            abs_diff(self.z, other.z),
+        );
+        dx * dx + dy * dy + dz * dz
+    }
+}
+
+macro_rules! impl_point_ops {
+    ($point:ident { $($field:ident),+ }) => {
+        impl<T: Add<Output = T>> Add for $point<T> {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self { $($field: self.$field + rhs.$field),+ }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $point<T> {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self { $($field: self.$field - rhs.$field),+ }
+            }
+        }
+
+        impl<T: Mul<Output = T> + Copy> Mul<T> for $point<T> {
+            type Output = Self;
+            fn mul(self, rhs: T) -> Self {
+                Self { $($field: self.$field * rhs),+ }
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $point<T> {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+
+        impl<T: AddAssign> AddAssign for $point<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                $(self.$field += rhs.$field;)+
+            }
+        }
+
+        impl<T: SubAssign> SubAssign for $point<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                $(self.$field -= rhs.$field;)+
+            }
+        }
+    };
+}
+
+impl_point_ops!(Point2 { x, y });
+impl_point_ops!(Point3 { x, y, z });
+
+/* -------------------------------------------------------------------------- */
+
+/// Sorted, de-duplicated coordinate values that map to dense indices.
+#[derive(Debug, Clone)]
+pub struct Compressed<T> {
+    values: Vec<T>,
+}
+
+impl<T: Ord + Copy> Compressed<T> {
+    pub fn new(values: impl IntoIterator<Item = T>) -> Self {
+        let mut values: Vec<T> = values.into_iter().collect();
+        values.sort_unstable();
+        values.dedup();
+        Self { values }
+    }
+
+    /// Dense index of `value`, if it is one of the compressed values.
+    pub fn index_of(&self, value: T) -> Option<usize> {
+        self.values.binary_search(&value).ok()
+    }
+
+    /// Original value of a dense index.
+    pub fn value(&self, index: usize) -> T {
+        self.values[index]
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+fn widen<T: Int>(p: Point2<T>) -> Point2<i128> {
+    let convert = |v: T| v.to_i128().expect("coordinate does not fit into i128");
+    Point2::new(convert(p.x), convert(p.y))
+}
+
+fn edges<T: Int>(
+    vertices: &[Point2<T>],
+) -> impl Iterator<Item = (Point2<i128>, Point2<i128>)> + '_ {
+    (0..vertices.len()).map(|i| {
+        (
+            widen(vertices[i]),
+            widen(vertices[(i + 1) % vertices.len()]),
+        )
+    })
+}
+
+/// Twice the area of a simple polygon given by its vertices in order (shoelace formula).
+///
+/// Doubling keeps the result exact for integer coordinates.
+pub fn polygon_area2<T: Int>(vertices: &[Point2<T>]) -> i128 {
+    edges(vertices)
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<i128>()
+        .abs()
+}
+
+/// Number of lattice points on the boundary of a polygon with integer vertices.
+pub fn boundary_points<T: Int>(vertices: &[Point2<T>]) -> i128 {
+    edges(vertices)
+        .map(|(a, b)| gcd((b.x - a.x).abs(), (b.y - a.y).abs()))
+        .sum()
+}
+
+/// Number of lattice points strictly inside a simple polygon with integer vertices, using Pick's
+/// theorem `A = I + B / 2 - 1`.
+pub fn interior_points<T: Int>(vertices: &[Point2<T>]) -> i128 {
+    (polygon_area2(vertices) - boundary_points(vertices) + 2) / 2
+}
+
+/// Where a point lies relative to a polygon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Locates `point` relative to a simple polygon given by its vertices in order.
+pub fn point_in_polygon<T: Int>(point: Point2<T>, vertices: &[Point2<T>]) -> Containment {
+    let p = widen(point);
+    let mut inside = false;
+
+    for (a, b) in edges(vertices) {
+        let cross = (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+        let within_box = p.x >= a.x.min(b.x)
+            && p.x <= a.x.max(b.x)
+            && p.y >= a.y.min(b.y)
+            && p.y <= a.y.max(b.y);
+        if cross == 0 && within_box {
+            return Containment::Boundary;
+        }
+
+        // half-open rule so that a ray through a vertex is only counted once.
+        if (a.y > p.y) != (b.y > p.y) {
+            let crosses_right = if b.y > a.y { cross > 0 } else { cross < 0 };
+            if crosses_right {
+                inside = !inside;
+            }
+        }
+    }
+
+    if inside {
+        Containment::Inside
+    } else {
+        Containment::Outside
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A rectilinear polygon rasterized onto the grid of its compressed vertex coordinates.
+///
+/// Every compressed coordinate gets its own row and column, and a gap row or column is inserted
+/// between neighbouring coordinates that have tiles between them. Each cell of this refined grid
+/// stands for a block of tiles that are either all covered (inside or on the boundary) or all
+/// outside, so containment queries never touch the full-resolution plane.
+#[derive(Debug, Clone)]
+pub struct RectilinearPolygon<T> {
+    xs: Compressed<T>,
+    ys: Compressed<T>,
+    /// Refined column and row of every compressed coordinate.
+    columns: Vec<usize>,
+    rows: Vec<usize>,
+    width: usize,
+    covered: Vec<bool>,
+    /// `outside_prefix[(y + 1) * (width + 1) + x + 1]` counts the outside cells in `0..=x, 0..=y`.
+    outside_prefix: Vec<u32>,
+}
+
+/// Refined indices of the compressed values and the size of the refined axis.
+fn refine<T: Int>(values: &Compressed<T>) -> (Vec<usize>, usize) {
+    let mut indices = Vec::with_capacity(values.len());
+    let mut next = 0;
+    for (i, &value) in values.values().iter().enumerate() {
+        // leave a gap for the tiles strictly between this value and the previous one.
+        if i > 0 && value - values.value(i - 1) > T::ONE {
+            next += 1;
+        }
+        indices.push(next);
+        next += 1;
+    }
+    (indices, next)
+}
+
+impl<T: Int> RectilinearPolygon<T> {
+    /// Builds the polygon from its vertices in order. Returns `None` for fewer than two vertices or
+    /// if any edge is not axis-aligned.
+    pub fn new(vertices: &[Point2<T>]) -> Option<Self> {
+        if vertices.len() < 2 {
+            return None;
+        }
+
+        let xs = Compressed::new(vertices.iter().map(|p| p.x));
+        let ys = Compressed::new(vertices.iter().map(|p| p.y));
+        let (columns, width) = refine(&xs);
+        let (rows, height) = refine(&ys);
+
+        // flood fill at double resolution: refined cell `(x, y)` is fill cell `(2x + 1, 2y + 1)`
+        // and the cells in between are seams, so the outside also reaches pockets whose only way
+        // out runs between two neighbouring walls. The outermost seams pad the polygon, so the
+        // outside is connected around it.
+        let (w, h) = (2 * width + 1, 2 * height + 1);
+        const OPEN: u8 = 0;
+        const WALL: u8 = 1;
+        const OUTSIDE: u8 = 2;
+        let mut grid = vec![OPEN; w * h];
+
+        for i in 0..vertices.len() {
+            let (a, b) = (vertices[i], vertices[(i + 1) % vertices.len()]);
+            let (ax, ay) = (columns[xs.index_of(a.x)?], rows[ys.index_of(a.y)?]);
+            let (bx, by) = (columns[xs.index_of(b.x)?], rows[ys.index_of(b.y)?]);
+            let (ax, ay, bx, by) = (2 * ax + 1, 2 * ay + 1, 2 * bx + 1, 2 * by + 1);
+
+            if ax == bx {
+                for y in ay.min(by)..=ay.max(by) {
+                    grid[y * w + ax] = WALL;
+                }
+            } else if ay == by {
+                grid[ay * w + ax.min(bx)..=ay * w + ax.max(bx)].fill(WALL);
+            } else {
+                return None;
+            }
+        }
+
+        let mut stack = vec![0];
+        grid[0] = OUTSIDE;
+        while let Some(idx) = stack.pop() {
+            let (x, y) = (idx % w, idx / w);
+            let neighbours = [
+                (x > 0).then(|| idx - 1),
+                (x + 1 < w).then(|| idx + 1),
+                (y > 0).then(|| idx - w),
+                (y + 1 < h).then(|| idx + w),
+            ];
+            for next in neighbours.into_iter().flatten() {
+                if grid[next] == OPEN {
+                    grid[next] = OUTSIDE;
+                    stack.push(next);
+                }
+            }
+        }
+
+        let covered: Vec<bool> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| grid[(2 * y + 1) * w + 2 * x + 1] != OUTSIDE)
+            .collect();
+
+        let stride = width + 1;
+        let mut outside_prefix = vec![0; stride * (height + 1)];
+        for y in 0..height {
+            for x in 0..width {
+                outside_prefix[(y + 1) * stride + x + 1] = outside_prefix[y * stride + x + 1]
+                    + outside_prefix[(y + 1) * stride + x]
+                    - outside_prefix[y * stride + x]
+                    + u32::from(!covered[y * width + x]);
+            }
+        }
+
+        Some(Self {
+            xs,
+            ys,
+            columns,
+            rows,
+            width,
+            covered,
+            outside_prefix,
+        })
+    }
+
+    pub fn xs(&self) -> &Compressed<T> {
+        &self.xs
+    }
+
+    pub fn ys(&self) -> &Compressed<T> {
+        &self.ys
+    }
+
+    /// Whether the compressed grid point `(xi, yi)` is inside or on the polygon.
+    pub fn is_covered(&self, xi: usize, yi: usize) -> bool {
+        self.covered[self.rows[yi] * self.width + self.columns[xi]]
+    }
+
+    /// Refined cell range `(x1, x2, y1, y2)` spanned by a rectangle with polygon coordinates.
+    fn refined_rect(&self, a: Point2<T>, b: Point2<T>) -> Option<(usize, usize, usize, usize)> {
+        let (x1, x2) = (
+            self.columns[self.xs.index_of(a.x)?],
+            self.columns[self.xs.index_of(b.x)?],
+        );
+        let (y1, y2) = (
+            self.rows[self.ys.index_of(a.y)?],
+            self.rows[self.ys.index_of(b.y)?],
+        );
+        Some((x1.min(x2), x1.max(x2), y1.min(y2), y1.max(y2)))
+    }
+
+    /// Whether the axis-aligned rectangle with opposite corners `a` and `b` lies fully inside the
+    /// polygon (boundary included). Both corners must use polygon coordinates, otherwise the
+    /// rectangle is reported as not contained.
+    ///
+    /// Runs in constant time by counting outside cells with a 2D prefix sum.
+    pub fn contains_rect(&self, a: Point2<T>, b: Point2<T>) -> bool {
+        let Some((x1, x2, y1, y2)) = self.refined_rect(a, b) else {
+            return false;
+        };
+        let stride = self.width + 1;
+        let at = |x: usize, y: usize| self.outside_prefix[y * stride + x];
+        at(x2 + 1, y2 + 1) + at(x1, y1) == at(x1, y2 + 1) + at(x2 + 1, y1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::{self, Rng};
+    use std::collections::HashSet;
+
+    fn polygon(points: &[(i64, i64)]) -> Vec<Point2<i64>> {
+        points.iter().map(|&(x, y)| Point2::new(x, y)).collect()
+    }
+
+    #[test]
+    fn point_operators() {
+        let a = Point2::new(1, 2);
+        let b = Point2::new(4, -2);
+        assert_eq!(a + b, Point2::new(5, 0));
+        assert_eq!(a - b, Point2::new(-3, 4));
+        assert_eq!(-(a * 3), Point2::new(-3, -6));
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.distance_squared(b), 25);
+
+        let mut p = Point3::new(1_u64, 2, 3);
+        p += Point3::new(1, 1, 1);
+        assert_eq!(p, Point3::new(2, 3, 4));
+        assert_eq!(p.distance_squared(Point3::new(0, 0, 0)), 29);
+        assert_eq!(Point3::new(5_u64, 0, 0).manhattan(Point3::new(0, 2, 7)), 14);
+    }
+
+    #[test]
+    fn compresses_coordinates() {
+        let compressed = Compressed::new([7, 2, 11, 7, 9]);
+        assert_eq!(compressed.values(), &[2, 7, 9, 11]);
+        assert_eq!(compressed.index_of(9), Some(2));
+        assert_eq!(compressed.index_of(8), None);
+        assert_eq!(compressed.value(3), 11);
+    }
+
+    #[test]
+    fn area_and_picks_theorem() {
+        let square = polygon(&[(0, 0), (4, 0), (4, 4), (0, 4)]);
+        assert_eq!(polygon_area2(&square), 32);
+        assert_eq!(boundary_points(&square), 16);
+        assert_eq!(interior_points(&square), 9);
+
+        let triangle = polygon(&[(0, 0), (6, 0), (0, 3)]);
+        assert_eq!(polygon_area2(&triangle), 18);
+        assert_eq!(boundary_points(&triangle), 12);
+        assert_eq!(interior_points(&triangle), 4);
+    }
+
+    #[test]
+    fn locates_points() {
+        let l_shape = polygon(&[(0, 0), (6, 0), (6, 2), (2, 2), (2, 6), (0, 6)]);
+        let locate = |x, y| point_in_polygon(Point2::new(x, y), &l_shape);
+        assert_eq!(locate(1, 1), Containment::Inside);
+        assert_eq!(locate(1, 5), Containment::Inside);
+        assert_eq!(locate(4, 4), Containment::Outside);
+        assert_eq!(locate(4, 2), Containment::Boundary);
+        assert_eq!(locate(0, 6), Containment::Boundary);
+        assert_eq!(locate(-1, 0), Containment::Outside);
+        assert_eq!(locate(7, 0), Containment::Outside);
+    }
+
+    #[test]
+    fn rectangles_inside_rectilinear_polygon() {
+        let l_shape = polygon(&[(0, 0), (6, 0), (6, 2), (2, 2), (2, 6), (0, 6)]);
+        let poly = RectilinearPolygon::new(&l_shape).unwrap();
+        let contains = |a: (i64, i64), b: (i64, i64)| {
+            poly.contains_rect(Point2::new(a.0, a.1), Point2::new(b.0, b.1))
+        };
+        assert!(contains((0, 0), (6, 2)));
+        assert!(contains((2, 6), (0, 0)));
+        assert!(!contains((0, 0), (6, 6)));
+        assert!(!contains((2, 2), (6, 6)));
+        assert!(!contains((0, 0), (1, 1)));
+
+        assert!(RectilinearPolygon::new(&polygon(&[(0, 0), (3, 3), (0, 3)])).is_none());
+    }
+
+    #[test]
+    fn rectangles_spanning_a_notch() {
+        // a U whose notch lies strictly between compressed coordinates.
+        let u_shape = polygon(&[
+            (0, 0),
+            (5, 0),
+            (5, 5),
+            (4, 5),
+            (4, 1),
+            (1, 1),
+            (1, 5),
+            (0, 5),
+        ]);
+        let poly = RectilinearPolygon::new(&u_shape).unwrap();
+        assert!(!poly.contains_rect(Point2::new(0, 5), Point2::new(5, 5)));
+        assert!(!poly.contains_rect(Point2::new(0, 1), Point2::new(5, 5)));
+        assert!(poly.contains_rect(Point2::new(0, 0), Point2::new(5, 1)));
+
+        // a notch one tile wide has no tiles outside the polygon.
+        let narrow = polygon(&[
+            (0, 0),
+            (3, 0),
+            (3, 4),
+            (2, 4),
+            (2, 1),
+            (1, 1),
+            (1, 4),
+            (0, 4),
+        ]);
+        let poly = RectilinearPolygon::new(&narrow).unwrap();
+        assert!(poly.contains_rect(Point2::new(0, 4), Point2::new(3, 0)));
+    }
+
+    /// Flood fills the outside of the polygon, and returns whether a tile is inside the polygon or
+    /// on its border. The fill runs at half-tile steps, so that it also reaches outside pockets
+    /// whose only way out runs between two border tiles.
+    fn fill(vertices: &[Point2<i64>]) -> impl Fn(i64, i64) -> bool {
+        let width = 2 * vertices.iter().map(|p| p.x).max().unwrap() + 1;
+        let height = 2 * vertices.iter().map(|p| p.y).max().unwrap() + 1;
+        let mut border = HashSet::new();
+        for (i, &a) in vertices.iter().enumerate() {
+            let b = vertices[(i + 1) % vertices.len()];
+            for x in 2 * a.x.min(b.x)..=2 * a.x.max(b.x) {
+                for y in 2 * a.y.min(b.y)..=2 * a.y.max(b.y) {
+                    border.insert((x, y));
+                }
+            }
+        }
+
+        let mut outside = HashSet::from([(-1, -1)]);
+        let mut stack = vec![(-1, -1)];
+        while let Some((x, y)) = stack.pop() {
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                let in_bounds = (-1..=width).contains(&nx) && (-1..=height).contains(&ny);
+                if in_bounds && !border.contains(&(nx, ny)) && outside.insert((nx, ny)) {
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        move |x, y| !outside.contains(&(2 * x, 2 * y))
+    }
+
+    #[test]
+    fn rect_containment_matches_fill() {
+        let mut rng = Rng::new(35);
+        for _ in 0..200 {
+            let vertices = generate::rectilinear_polygon(&mut rng);
+            let poly = RectilinearPolygon::new(&vertices).unwrap();
+            let covered = fill(&vertices);
+
+            let width = vertices.iter().map(|p| p.x).max().unwrap();
+            let height = vertices.iter().map(|p| p.y).max().unwrap();
+            for x in 0..=width {
+                for y in 0..=height {
+                    let containment = point_in_polygon(Point2::new(x, y), &vertices);
+                    assert_eq!(
+                        containment != Containment::Outside,
+                        covered(x, y),
+                        "{vertices:?} {x},{y}"
+                    );
+                }
+            }
+
+            for &a in &vertices {
+                for &b in &vertices {
+                    let expected = (a.x.min(b.x)..=a.x.max(b.x))
+                        .all(|x| (a.y.min(b.y)..=a.y.max(b.y)).all(|y| covered(x, y)));
+                    assert_eq!(
+                        poly.contains_rect(a, b),
+                        expected,
+                        "{vertices:?} {a:?} {b:?}"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Linear algebra over GF(2) with bitset rows.
+//!
+//! Systems are solved by Gauss-Jordan elimination where adding two rows is a word-wise XOR. The
+//! solution set of `A·x = b` is returned as one particular solution plus a basis of the null space,
+//! which is enough to find a minimum-weight solution by enumerating the `2^k` null space
+//! combinations instead of all `2^n` assignments.
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A fixed-length vector over GF(2).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitVec {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitVec {
+    /// The zero vector of length `len`.
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD_BITS)],
+            len,
+        }
+    }
+
+    pub fn from_bools(bits: &[bool]) -> Self {
+        let mut vec = Self::new(bits.len());
+        for (idx, _) in bits.iter().enumerate().filter(|(_, bit)| **bit) {
+            vec.set(idx, true);
+        }
+        vec
+    }
+
+    /// A vector of length `len` with the bits at `indices` set.
+    pub fn from_indices(len: usize, indices: impl IntoIterator<Item = usize>) -> Self {
+        let mut vec = Self::new(len);
+        for idx in indices {
+            vec.set(idx, true);
+        }
+        vec
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn get(&self, idx: usize) -> bool {
+        assert!(
+            idx < self.len,
+            "bit {idx} out of range for length {}",
+            self.len
+        );
+        self.words[idx / WORD_BITS] >> (idx % WORD_BITS) & 1 == 1
+    }
+
+    #[inline]
+    pub fn set(&mut self, idx: usize, value: bool) {
+        assert!(
+            idx < self.len,
+            "bit {idx} out of range for length {}",
+            self.len
+        );
+        let mask = 1 << (idx % WORD_BITS);
+        if value {
+            self.words[idx / WORD_BITS] |= mask;
+        } else {
+            self.words[idx / WORD_BITS] &= !mask;
+        }
+    }
+
+    #[inline]
+    pub fn flip(&mut self, idx: usize) {
+        assert!(
+            idx < self.len,
+            "bit {idx} out of range for length {}",
+            self.len
+        );
+        self.words[idx / WORD_BITS] ^= 1 << (idx % WORD_BITS);
+    }
+
+    /// Adds `other` to `self`, i.e. XORs them.
+    #[inline]
+    pub fn xor_assign(&mut self, other: &BitVec) {
+        assert_eq!(self.len, other.len, "length mismatch");
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word ^= other;
+        }
+    }
+
+    /// Number of set bits, i.e. the Hamming weight.
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Indices of the set bits in ascending order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(w, &word)| {
+            let mut rest = word;
+            std::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                Some(w * WORD_BITS + bit)
+            })
+        })
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// All solutions of a linear system: `particular + span(null_space)`.
+#[derive(Debug, Clone)]
+pub struct Solutions {
+    pub particular: BitVec,
+    pub null_space: Vec<BitVec>,
+}
+
+impl Solutions {
+    /// The solution with the fewest set bits. Enumerates all `2^k` combinations of the null space
+    /// basis in Gray code order, so each step costs a single XOR.
+    pub fn min_weight(&self) -> BitVec {
+        assert!(
+            self.null_space.len() < usize::BITS as usize,
+            "null space of dimension {} is too large to enumerate",
+            self.null_space.len()
+        );
+
+        let mut current = self.particular.clone();
+        let mut best = current.clone();
+        let mut best_weight = current.count_ones();
+
+        for step in 1..1_usize << self.null_space.len() {
+            current.xor_assign(&self.null_space[step.trailing_zeros() as usize]);
+            let weight = current.count_ones();
+            if weight < best_weight {
+                best_weight = weight;
+                best.clone_from(&current);
+            }
+        }
+
+        best
+    }
+}
+
+/// Solves `matrix · x = rhs` over `num_vars` variables, where `matrix` holds one row per equation
+/// and every row has one bit per variable. Returns `None` if the system is inconsistent.
+pub fn solve(matrix: &[BitVec], rhs: &BitVec, num_vars: usize) -> Option<Solutions> {
+    assert_eq!(matrix.len(), rhs.len(), "one right-hand side per equation");
+    assert!(
+        matrix.iter().all(|row| row.len() == num_vars),
+        "one bit per variable"
+    );
+
+    let mut rows: Vec<(BitVec, bool)> = matrix
+        .iter()
+        .cloned()
+        .zip((0..rhs.len()).map(|idx| rhs.get(idx)))
+        .collect();
+
+    let mut pivots = vec![];
+    for col in 0..num_vars {
+        let rank = pivots.len();
+        let Some(selected) = (rank..rows.len()).find(|&r| rows[r].0.get(col)) else {
+            continue;
+        };
+        rows.swap(rank, selected);
+
+        let (pivot_row, pivot_rhs) = rows[rank].clone();
+        for (r, (row, value)) in rows.iter_mut().enumerate() {
+            if r != rank && row.get(col) {
+                row.xor_assign(&pivot_row);
+                *value ^= pivot_rhs;
+            }
+        }
+        pivots.push(col);
+    }
+
+    // all remaining rows are zero on the left-hand side.
+    if rows[pivots.len()..].iter().any(|(_, value)| *value) {
+        return None;
+    }
+
+    let mut particular = BitVec::new(num_vars);
+    for (&col, (_, value)) in pivots.iter().zip(&rows) {
+        particular.set(col, *value);
+    }
+
+    let null_space = (0..num_vars)
+        .filter(|col| !pivots.contains(col))
+        .map(|free| {
+            let mut basis = BitVec::from_indices(num_vars, [free]);
+            for (&col, (row, _)) in pivots.iter().zip(&rows) {
+                if row.get(free) {
+                    basis.set(col, true);
+                }
+            }
+            basis
+        })
+        .collect();
+
+    Some(Solutions {
+        particular,
+        null_space,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::Rng;
+
+    fn multiply(matrix: &[BitVec], x: &BitVec) -> BitVec {
+        let bits: Vec<bool> = matrix
+            .iter()
+            .map(|row| row.ones().filter(|&idx| x.get(idx)).count() % 2 == 1)
+            .collect();
+        BitVec::from_bools(&bits)
+    }
+
+    #[test]
+    fn bit_vec_spans_words() {
+        let mut vec = BitVec::from_indices(130, [0, 63, 64, 129]);
+        assert_eq!(vec.count_ones(), 4);
+        assert_eq!(vec.ones().collect::<Vec<_>>(), [0, 63, 64, 129]);
+
+        vec.flip(64);
+        vec.set(100, true);
+        assert!(!vec.get(64));
+        assert!(vec.get(100));
+
+        vec.xor_assign(&vec.clone());
+        assert!(vec.is_zero());
+    }
+
+    #[test]
+    fn solves_toggle_system() {
+        // [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1)
+        let buttons: [&[usize]; 6] = [&[3], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]];
+        let matrix: Vec<BitVec> = (0..4)
+            .map(|light| {
+                let toggling = (0..buttons.len()).filter(|&b| buttons[b].contains(&light));
+                BitVec::from_indices(buttons.len(), toggling)
+            })
+            .collect();
+        let target = BitVec::from_bools(&[false, true, true, false]);
+
+        let solutions = solve(&matrix, &target, buttons.len()).unwrap();
+        assert_eq!(solutions.null_space.len(), 2);
+        let best = solutions.min_weight();
+        assert_eq!(best.count_ones(), 2);
+        assert_eq!(multiply(&matrix, &best), target);
+    }
+
+    #[test]
+    fn detects_inconsistent_system() {
+        let matrix = [
+            BitVec::from_indices(2, [0, 1]),
+            BitVec::from_indices(2, [0, 1]),
+        ];
+        assert!(solve(&matrix, &BitVec::from_bools(&[true, false]), 2).is_none());
+    }
+
+    #[test]
+    fn leaves_unconstrained_variables_free() {
+        let solutions = solve(&[], &BitVec::new(0), 3).unwrap();
+        assert_eq!(solutions.null_space.len(), 3);
+        assert!(solutions.min_weight().is_zero());
+        assert_eq!(solutions.min_weight().len(), 3);
+    }
+
+    #[test]
+    fn min_weight_matches_brute_force() {
+        let mut rng = Rng::new(32);
+
+        for _ in 0..200 {
+            let num_vars = 1 + rng.index(10);
+            let num_rows = 1 + rng.index(6);
+            let matrix: Vec<BitVec> = (0..num_rows)
+                .map(|_| {
+                    let bits = rng.next_u64();
+                    BitVec::from_indices(num_vars, (0..num_vars).filter(|b| bits >> b & 1 == 1))
+                })
+                .collect();
+            let rhs_bits = rng.next_u64();
+            let rhs =
+                BitVec::from_indices(num_rows, (0..num_rows).filter(|b| rhs_bits >> b & 1 == 1));
+
+            let expected = (0..1_u64 << num_vars)
+                .map(|mask| {
+                    BitVec::from_indices(num_vars, (0..num_vars).filter(|b| mask >> b & 1 == 1))
+                })
+                .filter(|x| multiply(&matrix, x) == rhs)
+                .map(|x| x.count_ones())
+                .min();
+
+            let actual = solve(&matrix, &rhs, num_vars).map(|solutions| {
+                let best = solutions.min_weight();
+                assert_eq!(multiply(&matrix, &best), rhs);
+                best.count_ones()
+            });
+            assert_eq!(actual, expected);
+        }
+    }
+}
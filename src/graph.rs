@@ -0,0 +1,170 @@
+//! Structural algorithms on dense directed graphs.
+//!
+//! Graphs are adjacency lists indexed by node: `adjacency[node]` holds the successors of `node`.
+
+/// Strongly connected components, found with an iterative version of Tarjan's algorithm.
+///
+/// Components are returned in reverse topological order of the condensation: no component has an
+/// edge into a component that comes after it.
+pub fn strongly_connected_components(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+
+    let num_nodes = adjacency.len();
+    let mut index = vec![UNVISITED; num_nodes];
+    let mut low_link = vec![0; num_nodes];
+    let mut on_stack = vec![false; num_nodes];
+    let mut stack = vec![];
+    let mut components = vec![];
+    let mut next_index = 0;
+
+    // (node, index of the next edge to explore)
+    let mut call_stack: Vec<(usize, usize)> = vec![];
+
+    for root in 0..num_nodes {
+        if index[root] != UNVISITED {
+            continue;
+        }
+
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        call_stack.push((root, 0));
+
+        while let Some(&(node, edge)) = call_stack.last() {
+            if let Some(&next) = adjacency[node].get(edge) {
+                if let Some(frame) = call_stack.last_mut() {
+                    frame.1 += 1;
+                }
+                if index[next] == UNVISITED {
+                    index[next] = next_index;
+                    low_link[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    call_stack.push((next, 0));
+                } else if on_stack[next] {
+                    low_link[node] = low_link[node].min(index[next]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                low_link[parent] = low_link[parent].min(low_link[node]);
+            }
+
+            if low_link[node] == index[node] {
+                let mut component = vec![];
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+/// A topological order of all nodes. If the graph has a cycle, the nodes of one strongly connected
+/// component containing it are returned instead.
+pub fn topological_order(adjacency: &[Vec<usize>]) -> Result<Vec<usize>, Vec<usize>> {
+    let mut components = strongly_connected_components(adjacency);
+
+    if let Some(cycle) = components
+        .iter()
+        .find(|component| component.len() > 1 || adjacency[component[0]].contains(&component[0]))
+    {
+        return Err(cycle.clone());
+    }
+
+    components.reverse();
+    Ok(components
+        .into_iter()
+        .map(|component| component[0])
+        .collect())
+}
+
+/// Nodes reachable from `start`, including `start` itself.
+pub fn reachable(adjacency: &[Vec<usize>], start: usize) -> Vec<bool> {
+    let mut seen = vec![false; adjacency.len()];
+    let mut stack = vec![start];
+    seen[start] = true;
+
+    while let Some(node) = stack.pop() {
+        for &next in &adjacency[node] {
+            if !seen[next] {
+                seen[next] = true;
+                stack.push(next);
+            }
+        }
+    }
+
+    seen
+}
+
+/// The graph with every edge reversed.
+pub fn reversed(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut reversed = vec![vec![]; adjacency.len()];
+    for (node, successors) in adjacency.iter().enumerate() {
+        for &next in successors {
+            reversed[next].push(node);
+        }
+    }
+    reversed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_components_in_reverse_topological_order() {
+        // 0 -> 1 <-> 2 -> 3 -> 4 -> 3, 5 isolated
+        let adjacency = vec![vec![1], vec![2], vec![1, 3], vec![4], vec![3], vec![]];
+        let mut components = strongly_connected_components(&adjacency);
+        for component in &mut components {
+            component.sort_unstable();
+        }
+
+        let position = |node: usize| components.iter().position(|c| c.contains(&node)).unwrap();
+        assert_eq!(components.len(), 4);
+        assert_eq!(components[position(1)], [1, 2]);
+        assert_eq!(components[position(3)], [3, 4]);
+        assert!(position(0) > position(1));
+        assert!(position(1) > position(3));
+    }
+
+    #[test]
+    fn orders_dags_and_reports_cycles() {
+        let dag = vec![vec![2], vec![0, 2], vec![], vec![1]];
+        let order = topological_order(&dag).unwrap();
+        let position = |node: usize| order.iter().position(|&n| n == node).unwrap();
+        for (node, successors) in dag.iter().enumerate() {
+            for &next in successors {
+                assert!(position(node) < position(next));
+            }
+        }
+
+        let mut cycle = topological_order(&[vec![1], vec![2], vec![1]]).unwrap_err();
+        cycle.sort_unstable();
+        assert_eq!(cycle, [1, 2]);
+        assert_eq!(topological_order(&[vec![0]]), Err(vec![0]));
+    }
+
+    #[test]
+    fn reachability_and_reversal() {
+        let adjacency = vec![vec![1], vec![2], vec![], vec![0]];
+        assert_eq!(reachable(&adjacency, 1), [false, true, true, false]);
+        assert_eq!(
+            reachable(&reversed(&adjacency), 1),
+            [true, true, false, true]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Dense two-dimensional grids stored in row-major order.
+//!
+//! Positions are `(x, y)` pairs with `x` growing to the right and `y` growing downwards, matching
+//! how puzzle inputs are laid out.
+use std::ops::{Index, IndexMut};
+
+use crate::parse::ParseError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A `width` x `height` grid with every cell set to `fill`.
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// A `width` x `height` grid with every cell computed from its position.
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut((usize, usize)) -> T) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(&mut cell)
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Builds a grid from the lines of `input`, converting every byte with `cell`. All lines must
+    /// have the same length.
+    pub fn parse(input: &str, mut cell: impl FnMut(u8) -> T) -> Result<Self, ParseError> {
+        let mut width = None;
+        let mut cells = vec![];
+        let mut height = 0;
+
+        for (i, line) in input.lines().enumerate() {
+            let expected = *width.get_or_insert(line.len());
+            if line.len() != expected {
+                let message = format!("expected {expected} cells, found {}", line.len());
+                return Err(ParseError::new(1, message).at_line(i + 1));
+            }
+            cells.extend(line.bytes().map(&mut cell));
+            height += 1;
+        }
+
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// All cells in row-major order.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<&T> {
+        (x < self.width && y < self.height).then(|| &self.cells[y * self.width + x])
+    }
+
+    pub fn get_mut(&mut self, (x, y): (usize, usize)) -> Option<&mut T> {
+        (x < self.width && y < self.height).then(|| &mut self.cells[y * self.width + x])
+    }
+
+    /// Row-major index of a position, for side tables indexed like [`Grid::cells`].
+    pub fn index_of(&self, (x, y): (usize, usize)) -> usize {
+        debug_assert!(
+            x < self.width && y < self.height,
+            "({x}, {y}) is out of bounds"
+        );
+        y * self.width + x
+    }
+
+    /// The position of a row-major index.
+    pub fn position(&self, idx: usize) -> (usize, usize) {
+        (idx % self.width, idx / self.width)
+    }
+
+    /// Every position in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a zero chunk size, and a grid without columns has no cells anyway.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// The up to eight positions around `(x, y)` that lie inside the grid.
+    pub fn neighbours(
+        &self,
+        (x, y): (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let xs = x.saturating_sub(1)..=(x + 1).min(self.width - 1);
+        let ys = y.saturating_sub(1)..=(y + 1).min(self.height - 1);
+        ys.flat_map(move |ny| xs.clone().map(move |nx| (nx, ny)))
+            .filter(move |&pos| pos != (x, y))
+    }
+
+    /// The up to four positions sharing an edge with `(x, y)` that lie inside the grid.
+    pub fn orthogonal_neighbours(
+        &self,
+        (x, y): (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let (width, height) = (self.width, self.height);
+        [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ]
+        .into_iter()
+        .filter(move |&(nx, ny)| nx < width && ny < height)
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is out of bounds"))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} is out of bounds"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_indexes() {
+        let grid = Grid::parse("ab\ncd\nef\n", |b| b).unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!(grid[(1, 2)], b'f');
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.position(grid.index_of((1, 1))), (1, 1));
+        assert_eq!(grid.rows().collect::<Vec<_>>(), [b"ab", b"cd", b"ef"]);
+
+        let err = Grid::parse("ab\nc\n", |b| b).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(1, "expected 2 cells, found 1").at_line(2)
+        );
+    }
+
+    #[test]
+    fn neighbours_stay_inside() {
+        let grid = Grid::new(3, 2, 0);
+        let mut around_corner: Vec<_> = grid.neighbours((0, 0)).collect();
+        around_corner.sort_unstable();
+        assert_eq!(around_corner, [(0, 1), (1, 0), (1, 1)]);
+        assert_eq!(grid.neighbours((1, 1)).count(), 5);
+
+        let mut orthogonal: Vec<_> = grid.orthogonal_neighbours((2, 1)).collect();
+        orthogonal.sort_unstable();
+        assert_eq!(orthogonal, [(1, 1), (2, 0)]);
+    }
+}
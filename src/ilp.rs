@@ -0,0 +1,461 @@
+//! Exact solver for small integer linear programs of the form
+//! `minimize c·x subject to A·x = b, 0 <= x <= u, x integer`.
+//!
+//! The equality system is first reduced over the rationals, which expresses every pivot variable
+//! in terms of a handful of free variables. The free variables are then enumerated within their
+//! bounds by a depth-first branch-and-bound that prunes on the objective and on the range each
+//! pivot variable can still reach. This is fast as long as the system has few degrees of freedom.
+use crate::num::{gcd, lcm};
+
+/// An optimal assignment returned by [`minimize`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// Value of the objective function.
+    pub value: i64,
+    /// Value of every variable, in column order.
+    pub values: Vec<i64>,
+}
+
+/// Minimizes `objective · x` subject to `matrix · x = rhs` and `0 <= x[j] <= upper[j]` over the
+/// integers. `matrix` has one row per equation and one column per variable.
+///
+/// Returns `None` if the system has no integer solution within the bounds.
+pub fn minimize(
+    matrix: &[Vec<i64>],
+    rhs: &[i64],
+    objective: &[i64],
+    upper: &[i64],
+) -> Option<Solution> {
+    let num_vars = objective.len();
+    assert_eq!(matrix.len(), rhs.len(), "one right-hand side per equation");
+    assert_eq!(upper.len(), num_vars, "one upper bound per variable");
+    assert!(
+        matrix.iter().all(|row| row.len() == num_vars),
+        "one coefficient per variable"
+    );
+
+    if upper.iter().any(|&bound| bound < 0) {
+        return None;
+    }
+
+    let reduced = reduce(matrix, rhs, num_vars)?;
+    let mut search = Search::new(reduced, objective, upper);
+    let mut residuals: Vec<i128> = search.rows.iter().map(|row| row.rhs).collect();
+    if search.is_feasible(0, &residuals) {
+        search.dfs(0, search.objective_const, 0, &mut residuals);
+    }
+
+    let (_, values) = search.best?;
+    let value = values.iter().zip(objective).map(|(x, c)| x * c).sum();
+    Some(Solution { value, values })
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A reduced equation `scale * x[pivot] + Σ free_coeffs[k] * x[free[k]] = rhs`.
+#[derive(Debug)]
+struct Row {
+    pivot: usize,
+    scale: i128,
+    free_coeffs: Vec<i128>,
+    rhs: i128,
+}
+
+#[derive(Debug)]
+struct Reduced {
+    rows: Vec<Row>,
+    free: Vec<usize>,
+}
+
+/// Gauss-Jordan elimination over the rationals. Each row is stored as an integer multiple of its
+/// rational form and divided by its content after every step to keep the entries small.
+/// Returns `None` for an inconsistent system.
+fn reduce(matrix: &[Vec<i64>], rhs: &[i64], num_vars: usize) -> Option<Reduced> {
+    let mut rows: Vec<Vec<i128>> = matrix
+        .iter()
+        .zip(rhs)
+        .map(|(row, &b)| row.iter().chain([&b]).map(|&v| v as i128).collect())
+        .collect();
+
+    let mut pivots = vec![];
+    for col in 0..num_vars {
+        let rank = pivots.len();
+        let Some(selected) = (rank..rows.len()).find(|&r| rows[r][col] != 0) else {
+            continue;
+        };
+        rows.swap(rank, selected);
+        if rows[rank][col] < 0 {
+            rows[rank].iter_mut().for_each(|v| *v = -*v);
+        }
+
+        let pivot_row = rows[rank].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if r == rank || factor == 0 {
+                continue;
+            }
+            for (v, &p) in row.iter_mut().zip(&pivot_row) {
+                *v = *v * pivot_row[col] - p * factor;
+            }
+            normalize(row);
+        }
+        pivots.push(col);
+    }
+
+    // all remaining rows are zero on the left-hand side.
+    if rows[pivots.len()..].iter().any(|row| row[num_vars] != 0) {
+        return None;
+    }
+
+    let free: Vec<usize> = (0..num_vars).filter(|col| !pivots.contains(col)).collect();
+    let rows = pivots
+        .iter()
+        .zip(&rows)
+        .map(|(&pivot, row)| Row {
+            pivot,
+            scale: row[pivot],
+            free_coeffs: free.iter().map(|&col| row[col]).collect(),
+            rhs: row[num_vars],
+        })
+        .collect();
+
+    Some(Reduced { rows, free })
+}
+
+fn ceil_div(a: i128, b: i128) -> i128 {
+    -(-a).div_euclid(b)
+}
+
+fn floor_div(a: i128, b: i128) -> i128 {
+    a.div_euclid(b)
+}
+
+fn normalize(row: &mut [i128]) {
+    let content = row.iter().fold(0, |acc, &v| gcd(acc, v));
+    if content > 1 {
+        row.iter_mut().for_each(|v| *v /= content);
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+struct Search {
+    rows: Vec<Row>,
+    free: Vec<usize>,
+    costs: Vec<i128>,
+    upper: Vec<i128>,
+    common: i128,
+    /// The objective multiplied by `lcm(scales)` is `objective_const + Σ free_costs[k] * x[free[k]]`.
+    objective_const: i128,
+    free_costs: Vec<i128>,
+    /// Lower bound of the scaled objective contributed by free variables `k..`.
+    min_rest: Vec<i128>,
+    /// Lower bound of the unscaled cost of free variables `k..` themselves.
+    free_min_rest: Vec<i128>,
+    /// Range of `Σ free_coeffs[k..] * x[free[k..]]` for every row.
+    row_ranges: Vec<Vec<(i128, i128)>>,
+    assignment: Vec<i128>,
+    best: Option<(i128, Vec<i64>)>,
+}
+
+impl Search {
+    fn new(reduced: Reduced, objective: &[i64], upper: &[i64]) -> Self {
+        let Reduced { mut rows, free } = reduced;
+        let upper: Vec<i128> = upper.iter().map(|&u| u as i128).collect();
+
+        // branching on the narrowest variables first keeps the top of the tree small.
+        let mut order: Vec<usize> = (0..free.len()).collect();
+        order.sort_by_key(|&k| upper[free[k]]);
+        let free: Vec<usize> = order.iter().map(|&k| free[k]).collect();
+        for row in &mut rows {
+            row.free_coeffs = order.iter().map(|&k| row.free_coeffs[k]).collect();
+        }
+        let costs: Vec<i128> = objective.iter().map(|&c| c as i128).collect();
+        let cost = |col: usize| costs[col];
+
+        let common = rows.iter().fold(1, |acc, row| lcm(acc, row.scale));
+        let objective_const = rows
+            .iter()
+            .map(|row| cost(row.pivot) * row.rhs * (common / row.scale))
+            .sum();
+        let free_costs: Vec<i128> = free
+            .iter()
+            .enumerate()
+            .map(|(k, &col)| {
+                let substituted: i128 = rows
+                    .iter()
+                    .map(|row| cost(row.pivot) * row.free_coeffs[k] * (common / row.scale))
+                    .sum();
+                cost(col) * common - substituted
+            })
+            .collect();
+
+        let mut min_rest = vec![0; free.len() + 1];
+        let mut free_min_rest = vec![0; free.len() + 1];
+        for k in (0..free.len()).rev() {
+            min_rest[k] = min_rest[k + 1] + (free_costs[k] * upper[free[k]]).min(0);
+            free_min_rest[k] = free_min_rest[k + 1] + (cost(free[k]) * upper[free[k]]).min(0);
+        }
+
+        let row_ranges = rows
+            .iter()
+            .map(|row| {
+                let mut ranges = vec![(0, 0); free.len() + 1];
+                for k in (0..free.len()).rev() {
+                    let term = row.free_coeffs[k] * upper[free[k]];
+                    let (lo, hi) = ranges[k + 1];
+                    ranges[k] = (lo + term.min(0), hi + term.max(0));
+                }
+                ranges
+            })
+            .collect();
+
+        Self {
+            assignment: vec![0; free.len()],
+            rows,
+            free,
+            costs,
+            upper,
+            common,
+            objective_const,
+            free_costs,
+            min_rest,
+            free_min_rest,
+            row_ranges,
+            best: None,
+        }
+    }
+
+    fn is_better(&self, bound: i128) -> bool {
+        self.best.as_ref().is_none_or(|(best, _)| bound < *best)
+    }
+
+    /// Whether every pivot variable can still land in its bounds once free variables `depth..`
+    /// are assigned.
+    fn is_feasible(&self, depth: usize, residuals: &[i128]) -> bool {
+        self.rows
+            .iter()
+            .zip(residuals)
+            .enumerate()
+            .all(|(r, (row, &residual))| {
+                let (lo, hi) = self.row_ranges[r][depth];
+                residual - hi <= row.scale * self.upper[row.pivot] && residual - lo >= 0
+            })
+    }
+
+    /// Lower bound of the unscaled objective contributed by the pivot variables, given the
+    /// ranges free variables `depth..` can still take.
+    fn pivot_bound(&self, depth: usize, residuals: &[i128]) -> i128 {
+        self.rows
+            .iter()
+            .zip(residuals)
+            .enumerate()
+            .map(|(r, (row, &residual))| {
+                let (lo, hi) = self.row_ranges[r][depth];
+                let cost = self.costs[row.pivot];
+                if cost >= 0 {
+                    cost * ceil_div(residual - hi, row.scale).max(0)
+                } else {
+                    cost * floor_div(residual - lo, row.scale).min(self.upper[row.pivot])
+                }
+            })
+            .sum()
+    }
+
+    /// Values of free variable `depth` that keep every pivot variable within its bounds.
+    fn candidate_range(&self, depth: usize, residuals: &[i128]) -> (i128, i128) {
+        let mut range = (0, self.upper[self.free[depth]]);
+        for (r, (row, &residual)) in self.rows.iter().zip(residuals).enumerate() {
+            let coeff = row.free_coeffs[depth];
+            if coeff == 0 {
+                continue;
+            }
+            let (lo, hi) = self.row_ranges[r][depth + 1];
+            // residual - coeff * value - rest must lie in 0..=scale * upper for some rest in lo..=hi.
+            let (min, max) = (
+                residual - hi - row.scale * self.upper[row.pivot],
+                residual - lo,
+            );
+            let (min, max) = if coeff > 0 { (min, max) } else { (-max, -min) };
+            range.0 = range.0.max(ceil_div(min, coeff.abs()));
+            range.1 = range.1.min(floor_div(max, coeff.abs()));
+        }
+        range
+    }
+
+    fn dfs(&mut self, depth: usize, partial: i128, direct: i128, residuals: &mut [i128]) {
+        if depth == self.free.len() {
+            self.record(partial, residuals);
+            return;
+        }
+
+        let cost = self.free_costs[depth];
+        let (min, max) = self.candidate_range(depth, residuals);
+        if min > max {
+            return;
+        }
+        let candidates: Box<dyn Iterator<Item = i128>> = if cost >= 0 {
+            Box::new(min..=max)
+        } else {
+            Box::new((min..=max).rev())
+        };
+
+        // candidates are visited in order of increasing objective, so the first value that cannot
+        // beat the incumbent ends the loop.
+        for value in candidates {
+            let next = partial + cost * value;
+            if !self.is_better(next + self.min_rest[depth + 1]) {
+                break;
+            }
+
+            for (residual, row) in residuals.iter_mut().zip(&self.rows) {
+                *residual -= row.free_coeffs[depth] * value;
+            }
+            let next_direct = direct + self.costs[self.free[depth]] * value;
+            let bound = next_direct
+                + self.free_min_rest[depth + 1]
+                + self.pivot_bound(depth + 1, residuals);
+            if self.is_better(bound * self.common) {
+                self.assignment[depth] = value;
+                self.dfs(depth + 1, next, next_direct, residuals);
+            }
+            for (residual, row) in residuals.iter_mut().zip(&self.rows) {
+                *residual += row.free_coeffs[depth] * value;
+            }
+        }
+    }
+
+    fn record(&mut self, scaled_value: i128, residuals: &[i128]) {
+        if !self.is_better(scaled_value) {
+            return;
+        }
+        if self
+            .rows
+            .iter()
+            .zip(residuals)
+            .any(|(row, residual)| residual % row.scale != 0)
+        {
+            return;
+        }
+
+        let num_vars = self.rows.len() + self.free.len();
+        let mut values = vec![0; num_vars];
+        for (&col, &value) in self.free.iter().zip(&self.assignment) {
+            values[col] = value as i64;
+        }
+        for (row, &residual) in self.rows.iter().zip(residuals) {
+            values[row.pivot] = (residual / row.scale) as i64;
+        }
+        self.best = Some((scaled_value, values));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::Rng;
+
+    /// Exhaustive search over the bounding box as a reference.
+    fn brute_force(
+        matrix: &[Vec<i64>],
+        rhs: &[i64],
+        objective: &[i64],
+        upper: &[i64],
+    ) -> Option<i64> {
+        let mut values = vec![0; objective.len()];
+        let mut best = None;
+        loop {
+            let satisfied = matrix
+                .iter()
+                .zip(rhs)
+                .all(|(row, &b)| row.iter().zip(&values).map(|(a, x)| a * x).sum::<i64>() == b);
+            if satisfied {
+                let value: i64 = values.iter().zip(objective).map(|(x, c)| x * c).sum();
+                best = Some(best.map_or(value, |b: i64| b.min(value)));
+            }
+
+            let Some(k) = (0..values.len()).find(|&k| values[k] < upper[k]) else {
+                return best;
+            };
+            values[k] += 1;
+            values[..k].fill(0);
+        }
+    }
+
+    fn check(matrix: &[Vec<i64>], rhs: &[i64], objective: &[i64], upper: &[i64]) {
+        let solution = minimize(matrix, rhs, objective, upper);
+        assert_eq!(
+            solution.as_ref().map(|s| s.value),
+            brute_force(matrix, rhs, objective, upper),
+            "{matrix:?} x = {rhs:?}, min {objective:?}, x <= {upper:?}"
+        );
+        if let Some(Solution { values, .. }) = solution {
+            for (row, &b) in matrix.iter().zip(rhs) {
+                assert_eq!(row.iter().zip(&values).map(|(a, x)| a * x).sum::<i64>(), b);
+            }
+            assert!(values.iter().zip(upper).all(|(x, u)| (0..=*u).contains(x)));
+        }
+    }
+
+    #[test]
+    fn solves_button_system() {
+        // (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
+        let matrix = vec![
+            vec![0, 0, 0, 0, 1, 1],
+            vec![0, 1, 0, 0, 0, 1],
+            vec![0, 0, 1, 1, 1, 0],
+            vec![1, 1, 0, 1, 0, 0],
+        ];
+        let solution = minimize(&matrix, &[3, 5, 4, 7], &[1; 6], &[7, 5, 4, 4, 3, 3]).unwrap();
+        assert_eq!(solution.value, 10);
+        check(&matrix, &[3, 5, 4, 7], &[1; 6], &[7, 5, 4, 4, 3, 3]);
+    }
+
+    #[test]
+    fn detects_infeasible_systems() {
+        // inconsistent equations.
+        assert_eq!(
+            minimize(&[vec![1, 1], vec![1, 1]], &[1, 2], &[1, 1], &[5, 5]),
+            None
+        );
+        // only fractional solutions.
+        assert_eq!(minimize(&[vec![2]], &[3], &[1], &[5]), None);
+        // solution outside the bounds.
+        assert_eq!(minimize(&[vec![1, 1]], &[9], &[1, 1], &[4, 4]), None);
+    }
+
+    #[test]
+    fn handles_negative_coefficients_and_costs() {
+        check(&[vec![1, -1, 2]], &[3], &[1, 1, 1], &[6, 6, 6]);
+        check(&[vec![1, 1, 1]], &[6], &[2, -1, 3], &[6, 6, 6]);
+        check(
+            &[vec![3, 0, -2], vec![0, 2, 1]],
+            &[2, 9],
+            &[1, 1, 1],
+            &[8, 8, 8],
+        );
+    }
+
+    #[test]
+    fn matches_brute_force_on_generated_systems() {
+        let mut rng = Rng::new(31);
+        let mut next = |bound: u64| rng.below(bound) as i64;
+
+        for _ in 0..200 {
+            let num_vars = 1 + next(5) as usize;
+            let num_rows = 1 + next(3) as usize;
+            let upper: Vec<i64> = (0..num_vars).map(|_| next(5)).collect();
+            let objective: Vec<i64> = (0..num_vars).map(|_| next(4)).collect();
+            let matrix: Vec<Vec<i64>> = (0..num_rows)
+                .map(|_| (0..num_vars).map(|_| next(3)).collect())
+                .collect();
+            // derive the right-hand side from a point in the box so most systems are feasible.
+            let point: Vec<i64> = upper.iter().map(|&u| next(u as u64 + 1)).collect();
+            let rhs: Vec<i64> = matrix
+                .iter()
+                .map(|row| row.iter().zip(&point).map(|(a, x)| a * x).sum())
+                .collect();
+            check(&matrix, &rhs, &objective, &upper);
+        }
+    }
+}
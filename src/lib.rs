@@ -1,3 +1,19 @@
 pub mod template;
 
 // Use this file to add helper functions and additional modules.
+pub mod explain;
+pub mod generate;
+pub mod geom;
+pub mod gf2;
+pub mod graph;
+pub mod grid;
+pub mod ilp;
+pub mod num;
+pub mod ocr;
+pub mod packing;
+pub mod parse;
+pub mod record;
+pub mod search;
+pub mod spatial;
+pub mod verify;
+pub mod viz;
@@ -1,4 +1,6 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve, time};
+use advent_of_code::template::commands::{
+    all, download, generate, read, scaffold, solve, time, verify,
+};
 use args::{AppArguments, parse};
 
 #[cfg(feature = "today")]
@@ -8,6 +10,9 @@ use std::process;
 
 mod args {
     use advent_of_code::template::Day;
+    use advent_of_code::template::commands::solve::SolveOptions;
+    use advent_of_code::viz;
+    use std::path::PathBuf;
     use std::process;
 
     pub enum AppArguments {
@@ -24,9 +29,7 @@ mod args {
         },
         Solve {
             day: Day,
-            release: bool,
-            dhat: bool,
-            submit: Option<u8>,
+            options: SolveOptions,
         },
         All {
             release: bool,
@@ -35,6 +38,15 @@ mod args {
             all: bool,
             day: Option<Day>,
             store: bool,
+            generated: bool,
+        },
+        Verify {
+            day: Option<Day>,
+        },
+        Generate {
+            day: Day,
+            size: f64,
+            seed: u64,
         },
         #[cfg(feature = "today")]
         Today,
@@ -50,13 +62,23 @@ mod args {
             Some("time") => {
                 let all = args.contains("--all");
                 let store = args.contains("--store");
+                let generated = args.contains("--generated");
 
                 AppArguments::Time {
                     all,
                     day: args.opt_free_from_str()?,
                     store,
+                    generated,
                 }
             }
+            Some("verify") => AppArguments::Verify {
+                day: args.opt_free_from_str()?,
+            },
+            Some("gen") => AppArguments::Generate {
+                size: args.opt_value_from_str("--size")?.unwrap_or(1.0),
+                seed: args.opt_value_from_str("--seed")?.unwrap_or(0),
+                day: args.free_from_str()?,
+            },
             Some("download") => AppArguments::Download {
                 day: args.free_from_str()?,
             },
@@ -68,12 +90,35 @@ mod args {
                 download: args.contains("--download"),
                 overwrite: args.contains("--overwrite"),
             },
-            Some("solve") => AppArguments::Solve {
-                day: args.free_from_str()?,
-                release: args.contains("--release"),
-                submit: args.opt_value_from_str("--submit")?,
-                dhat: args.contains("--dhat"),
-            },
+            Some("solve") => {
+                let show = args.contains("--visualize");
+                let record: Option<PathBuf> = args.opt_value_from_str("--record")?;
+                let fps = args.opt_value_from_str("--fps")?;
+                let paused = args.contains("--step");
+
+                let options = SolveOptions {
+                    release: args.contains("--release"),
+                    submit: args.opt_value_from_str("--submit")?,
+                    dhat: args.contains("--dhat"),
+                    visualize: (show || record.is_some()).then(|| viz::Options {
+                        fps: fps.unwrap_or(viz::DEFAULT_FPS),
+                        paused,
+                        show,
+                        record,
+                    }),
+                    explain: args.contains("--explain"),
+                    json: args.contains("--json"),
+                    profile_lines: args.contains("--profile-lines"),
+                    slowest: args.opt_value_from_str("--slowest")?,
+                    generated: args.contains("--generated"),
+                    ..SolveOptions::default()
+                };
+
+                AppArguments::Solve {
+                    day: args.free_from_str()?,
+                    options,
+                }
+            }
             #[cfg(feature = "today")]
             Some("today") => AppArguments::Today,
             Some(x) => {
@@ -103,7 +148,14 @@ fn main() {
         }
         Ok(args) => match args {
             AppArguments::All { release } => all::handle(release),
-            AppArguments::Time { day, all, store } => time::handle(day, all, store),
+            AppArguments::Time {
+                day,
+                all,
+                store,
+                generated,
+            } => time::handle(day, all, store, generated),
+            AppArguments::Verify { day } => verify::handle(day),
+            AppArguments::Generate { day, size, seed } => generate::handle(day, size, seed),
             AppArguments::Download { day } => download::handle(day),
             AppArguments::Read { day } => read::handle(day),
             AppArguments::Scaffold {
@@ -116,12 +168,7 @@ fn main() {
                     download::handle(day);
                 }
             }
-            AppArguments::Solve {
-                day,
-                release,
-                dhat,
-                submit,
-            } => solve::handle(day, release, dhat, submit),
+            AppArguments::Solve { day, options } => solve::handle(day, options),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
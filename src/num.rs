@@ -0,0 +1,401 @@
+//! Integer and digit helpers, generic over the primitive integer types.
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Rem, Sub};
+
+/// Primitive integers the helpers in this module operate on.
+pub trait Int:
+    Copy
+    + Ord
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const TEN: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn to_i128(self) -> Option<i128>;
+    fn from_i128(value: i128) -> Option<Self>;
+}
+
+macro_rules! impl_int {
+    ($($t:ty),*) => {
+        $(
+            impl Int for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const TEN: Self = 10;
+
+                #[inline]
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                #[inline]
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+
+                #[inline]
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+
+                #[inline]
+                fn to_i128(self) -> Option<i128> {
+                    i128::try_from(self).ok()
+                }
+
+                #[inline]
+                fn from_i128(value: i128) -> Option<Self> {
+                    Self::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_int!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+/* -------------------------------------------------------------------------- */
+
+/// Number of decimal digits of `n`, ignoring the sign. Zero has one digit.
+pub fn digit_len<T: Int>(n: T) -> u32 {
+    let mut n = n / T::TEN;
+    let mut len = 1;
+    while n != T::ZERO {
+        n = n / T::TEN;
+        len += 1;
+    }
+    len
+}
+
+/// `10^exp`. Panics if the result does not fit into `T`.
+pub fn pow10<T: Int>(exp: u32) -> T {
+    (0..exp).fold(T::ONE, |acc, _| {
+        acc.checked_mul(T::TEN).expect("power of ten overflows")
+    })
+}
+
+/// Iterates the decimal digits of `n` from most to least significant, ignoring the sign.
+pub fn digits<T: Int>(n: T) -> Digits<T> {
+    let len = digit_len(n);
+    Digits {
+        n,
+        divisor: pow10(len - 1),
+    }
+}
+
+/// Iterator returned by [`digits`].
+pub struct Digits<T> {
+    n: T,
+    divisor: T,
+}
+
+impl<T: Int> Iterator for Digits<T> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.divisor == T::ZERO {
+            return None;
+        }
+        let digit = (self.n / self.divisor % T::TEN).to_i128()?.unsigned_abs() as u8;
+        self.divisor = self.divisor / T::TEN;
+        Some(digit)
+    }
+}
+
+/// Splits `n` into the digits above and the lowest `low_digits` digits, e.g. `1234 -> (12, 34)`.
+pub fn split_digits<T: Int>(n: T, low_digits: u32) -> (T, T) {
+    let divisor = pow10::<T>(low_digits);
+    (n / divisor, n % divisor)
+}
+
+/// The repunit with `len` ones, e.g. `1111` for a length of four.
+pub fn repunit<T: Int>(len: u32) -> T {
+    repeat_multiplier(1, len)
+}
+
+/// Multiplier that repeats a `block_len` digit block `times` times, e.g. `10101` for `(2, 3)`.
+pub fn repeat_multiplier<T: Int>(block_len: u32, times: u32) -> T {
+    let shift = pow10::<T>(block_len);
+    (0..times).fold(T::ZERO, |acc, _| acc * shift + T::ONE)
+}
+
+/// Writes `block` (padded to `block_len` digits) `times` times in a row, e.g. `(12, 2, 3) -> 121212`.
+pub fn repeat_block<T: Int>(block: T, block_len: u32, times: u32) -> T {
+    block * repeat_multiplier(block_len, times)
+}
+
+/// Returns whether the digits of `n` consist of the same block repeated exactly `times` times.
+pub fn is_repeated<T: Int>(n: T, times: u32) -> bool {
+    let len = digit_len(n);
+    if times == 0 || !len.is_multiple_of(times) {
+        return false;
+    }
+    let block_len = len / times;
+    let block = n % pow10::<T>(block_len);
+    // the multiplier never exceeds `n`, but repeating a wrong block can overflow.
+    block.checked_mul(repeat_multiplier(block_len, times)) == Some(n)
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Largest digit count of any supported integer (`u128::MAX` has 39 digits).
+pub const MAX_DIGITS: usize = 39;
+
+/// Divisors of a digit count in a fixed-size buffer, so tables of them can be built in `const`.
+#[derive(Debug, Clone, Copy)]
+pub struct SmallDivisors {
+    len: usize,
+    values: [u8; 8],
+}
+
+impl SmallDivisors {
+    pub const fn as_slice(&self) -> &[u8] {
+        self.values.split_at(self.len).0
+    }
+}
+
+const fn non_one_divisors_table() -> [SmallDivisors; MAX_DIGITS + 1] {
+    let mut table = [SmallDivisors {
+        len: 0,
+        values: [0; 8],
+    }; MAX_DIGITS + 1];
+    let mut n = 1;
+    while n <= MAX_DIGITS {
+        let mut d = 2;
+        while d <= n {
+            if n.is_multiple_of(d) {
+                let entry = &mut table[n];
+                entry.values[entry.len] = d as u8;
+                entry.len += 1;
+            }
+            d += 1;
+        }
+        n += 1;
+    }
+    table
+}
+
+/// For every digit count `n`, its divisors greater than one in ascending order. These are the
+/// numbers of times a block can be repeated to form an `n` digit number.
+pub const NON_ONE_DIVISORS: [SmallDivisors; MAX_DIGITS + 1] = non_one_divisors_table();
+
+/* -------------------------------------------------------------------------- */
+
+/// All positive divisors of `n` in ascending order.
+pub fn divisors<T: Int>(n: T) -> Vec<T> {
+    let mut low = vec![];
+    let mut high = vec![];
+    let mut d = T::ONE;
+    while d.checked_mul(d).is_some_and(|square| square <= n) {
+        if n % d == T::ZERO {
+            low.push(d);
+            if d != n / d {
+                high.push(n / d);
+            }
+        }
+        d = d + T::ONE;
+    }
+    low.extend(high.into_iter().rev());
+    low
+}
+
+/// Greatest common divisor, always non-negative.
+pub fn gcd<T: Int>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a, b);
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    if a < T::ZERO { T::ZERO - a } else { a }
+}
+
+/// Least common multiple, always non-negative.
+pub fn lcm<T: Int>(a: T, b: T) -> T {
+    if a == T::ZERO || b == T::ZERO {
+        return T::ZERO;
+    }
+    let l = a / gcd(a, b) * b;
+    if l < T::ZERO { T::ZERO - l } else { l }
+}
+
+/// The Möbius function: `0` if `n` has a squared prime factor, otherwise `-1` or `1` for an odd or
+/// even number of prime factors. Panics for non-positive `n`.
+pub fn mobius<T: Int>(n: T) -> i32 {
+    assert!(n > T::ZERO, "Möbius function of a non-positive number");
+    let mut rest = n;
+    let mut sign = 1;
+    let mut p = T::ONE + T::ONE;
+    while p.checked_mul(p).is_some_and(|square| square <= rest) {
+        if rest % p == T::ZERO {
+            rest = rest / p;
+            if rest % p == T::ZERO {
+                return 0;
+            }
+            sign = -sign;
+        }
+        p = p + T::ONE;
+    }
+    if rest > T::ONE { -sign } else { sign }
+}
+
+/// Returns `(g, x, y)` with `a * x + b * y = g = gcd(a, b)`.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+        (old_t, t) = (t, old_t - q * t);
+    }
+    (old_r, old_s, old_t)
+}
+
+/// Inverse of `a` modulo `m`, if `a` and `m` are coprime.
+pub fn mod_inv<T: Int>(a: T, m: T) -> Option<T> {
+    let m = m.to_i128().filter(|&m| m > 0)?;
+    let a = a.to_i128()?.rem_euclid(m);
+    let (g, x, _) = extended_gcd(a, m);
+    if g != 1 {
+        return None;
+    }
+    T::from_i128(x.rem_euclid(m))
+}
+
+/// Chinese remainder theorem over `(residue, modulus)` pairs. The moduli do not need to be
+/// coprime. Returns the smallest non-negative solution and the combined modulus, or `None` if the
+/// congruences contradict each other or the result overflows.
+pub fn crt<T: Int>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut residue: i128 = 0;
+    let mut modulus: i128 = 1;
+    for &(r, m) in congruences {
+        let m = m.to_i128().filter(|&m| m > 0)?;
+        let r = r.to_i128()?.rem_euclid(m);
+        let (g, p, _) = extended_gcd(modulus, m);
+        let diff = r - residue;
+        if diff % g != 0 {
+            return None;
+        }
+        let step = m / g;
+        let k = (diff / g % step).checked_mul(p % step)?.rem_euclid(step);
+        residue = residue.checked_add(modulus.checked_mul(k)?)?;
+        modulus = modulus.checked_mul(step)?;
+        residue = residue.rem_euclid(modulus);
+    }
+    Some((T::from_i128(residue)?, T::from_i128(modulus)?))
+}
+
+/// Integer square root, i.e. the largest `r` with `r * r <= n`. Panics for negative `n`.
+pub fn isqrt<T: Int>(n: T) -> T {
+    assert!(n >= T::ZERO, "square root of a negative number");
+    let two = T::ONE + T::ONE;
+    if n < two {
+        return n;
+    }
+    // Newton's method converges monotonically from any starting point above the root.
+    let mut x = n / two + T::ONE;
+    let mut y = (x + n / x) / two;
+    while y < x {
+        x = y;
+        y = (x + n / x) / two;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_helpers() {
+        assert_eq!(digit_len(0_u64), 1);
+        assert_eq!(digit_len(9_u64), 1);
+        assert_eq!(digit_len(10_u64), 2);
+        assert_eq!(digit_len(u64::MAX), 20);
+        assert_eq!(digit_len(u128::MAX), MAX_DIGITS as u32);
+        assert_eq!(digit_len(-12345_i64), 5);
+        assert_eq!(digits(90210_u64).collect::<Vec<_>>(), [9, 0, 2, 1, 0]);
+        assert_eq!(digits(-42_i64).collect::<Vec<_>>(), [4, 2]);
+        assert_eq!(split_digits(123456_u64, 2), (1234, 56));
+    }
+
+    #[test]
+    fn repeated_patterns() {
+        assert_eq!(repunit::<u64>(4), 1111);
+        assert_eq!(repeat_multiplier::<u64>(2, 3), 10101);
+        assert_eq!(repeat_block(12_u64, 2, 3), 121212);
+        assert_eq!(repeat_block(7_u128, 3, 2), 7007);
+        assert!(is_repeated(123123_u64, 2));
+        assert!(is_repeated(1111111_i64, 7));
+        assert!(!is_repeated(1231234_u64, 2));
+        assert!(!is_repeated(12341234_u64, 4));
+        assert!(!is_repeated(10_000_000_000_000_000_000_u64, 20));
+    }
+
+    #[test]
+    fn non_one_divisors_table() {
+        assert_eq!(NON_ONE_DIVISORS[1].as_slice(), &[] as &[u8]);
+        assert_eq!(NON_ONE_DIVISORS[6].as_slice(), &[2, 3, 6]);
+        assert_eq!(NON_ONE_DIVISORS[12].as_slice(), &[2, 3, 4, 6, 12]);
+        assert_eq!(NON_ONE_DIVISORS[20].as_slice(), &[2, 4, 5, 10, 20]);
+        assert_eq!(
+            NON_ONE_DIVISORS[36].as_slice(),
+            &[2, 3, 4, 6, 9, 12, 18, 36]
+        );
+        for (n, entry) in NON_ONE_DIVISORS.iter().enumerate().skip(1) {
+            let expected: Vec<u8> = divisors(n as u8).into_iter().skip(1).collect();
+            assert_eq!(entry.as_slice(), expected);
+        }
+    }
+
+    #[test]
+    fn divisors_gcd_lcm() {
+        assert_eq!(divisors(1_u64), [1]);
+        assert_eq!(divisors(36_u64), [1, 2, 3, 4, 6, 9, 12, 18, 36]);
+        assert_eq!(divisors(97_i64), [1, 97]);
+        assert_eq!(gcd(12_u64, 18), 6);
+        assert_eq!(gcd(-12_i64, 18), 6);
+        assert_eq!(gcd(0_u128, 5), 5);
+        assert_eq!(lcm(4_u64, 6), 12);
+        assert_eq!(lcm(-4_i64, 6), 12);
+    }
+
+    #[test]
+    fn mobius_function() {
+        let values: Vec<i32> = (1..=12_u64).map(mobius).collect();
+        assert_eq!(values, [1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0]);
+        assert_eq!(mobius(30_i64), -1);
+        assert_eq!(mobius(97_u8), -1);
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        assert_eq!(mod_inv(3_u64, 11), Some(4));
+        assert_eq!(mod_inv(-3_i64, 11), Some(7));
+        assert_eq!(mod_inv(6_u64, 9), None);
+        assert_eq!(crt(&[(2_u64, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1_i64, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(0_u64, 4), (1, 6)]), None);
+    }
+
+    #[test]
+    fn integer_square_root() {
+        assert_eq!(isqrt(0_u64), 0);
+        assert_eq!(isqrt(1_u64), 1);
+        assert_eq!(isqrt(15_u64), 3);
+        assert_eq!(isqrt(16_i64), 4);
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+    }
+}
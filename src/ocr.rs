@@ -0,0 +1,215 @@
+//! Reads answers drawn in Advent of Code's block-letter fonts.
+//!
+//! Some puzzles answer with a picture of capital letters, either 6 pixels tall (about 4 wide) or 10
+//! pixels tall (about 6 wide). Letters are told apart by the blank columns between them, so their
+//! exact offsets and widths don't matter. Lit pixels can be `#` or `█`, dark ones `.` or spaces.
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::grid::Grid;
+
+const SMALL_LETTERS: &str = "ABCEFGHIJKLOPRSUYZ";
+const SMALL_FONT: [&str; 6] = [
+    ".##..###...##..####.####..##..#..#.###...##.#..#.#.....##..###..###...###.#..#.#...#.####",
+    "#..#.#..#.#..#.#....#....#..#.#..#..#.....#.#.#..#....#..#.#..#.#..#.#....#..#.#...#....#",
+    "#..#.###..#....###..###..#....####..#.....#.##...#....#..#.#..#.#..#.#....#..#..#.#....#.",
+    "####.#..#.#....#....#....#.##.#..#..#.....#.#.#..#....#..#.###..###...##..#..#...#....#..",
+    "#..#.#..#.#..#.#....#....#..#.#..#..#..#..#.#.#..#....#..#.#....#.#.....#.#..#...#...#...",
+    "#..#.###...##..####.#.....###.#..#.###..##..#..#.####..##..#....#..#.###...##....#...####",
+];
+
+const LARGE_LETTERS: &str = "ABCEFGHJKLNPRXZ";
+const LARGE_FONT: [&str; 10] = [
+    "..##...#####...####..######.######..####..#....#....###.#....#.#......#....#.#####..#####..#....#.######",
+    ".#..#..#....#.#....#.#......#......#....#.#....#.....#..#...#..#......##...#.#....#.#....#.#....#......#",
+    "#....#.#....#.#......#......#......#......#....#.....#..#..#...#......##...#.#....#.#....#..#..#.......#",
+    "#....#.#....#.#......#......#......#......#....#.....#..#.#....#......#.#..#.#....#.#....#..#..#......#.",
+    "#....#.#####..#......#####..#####..#......######.....#..##.....#......#.#..#.#####..#####....##......#..",
+    "######.#....#.#......#......#......#..###.#....#.....#..##.....#......#..#.#.#......#..#.....##.....#...",
+    "#....#.#....#.#......#......#......#....#.#....#.....#..#.#....#......#..#.#.#......#...#...#..#...#....",
+    "#....#.#....#.#......#......#......#....#.#....#.#...#..#..#...#......#...##.#......#...#...#..#..#.....",
+    "#....#.#....#.#....#.#......#......#...##.#....#.#...#..#...#..#......#...##.#......#....#.#....#.#.....",
+    "#....#.#####...####..######.#.......###.#.#....#..###...#....#.######.#....#.#......#....#.#....#.######",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// The text contains characters other than pixels, so it isn't a drawing at all.
+    NotAPicture,
+    /// The letters are neither 6 nor 10 pixels tall.
+    UnsupportedHeight(usize),
+    /// A glyph that isn't in the font, with the 0-based column where it starts.
+    UnknownGlyph { column: usize, glyph: String },
+}
+
+impl Error for OcrError {}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OcrError::NotAPicture => write!(f, "not a picture of letters"),
+            OcrError::UnsupportedHeight(height) => {
+                write!(f, "letters are {height} pixels tall, expected 6 or 10")
+            }
+            OcrError::UnknownGlyph { column, glyph } => {
+                write!(f, "unknown glyph at column {column}:\n{glyph}")
+            }
+        }
+    }
+}
+
+/// Reads the letters in a picture where `true` is a lit pixel. Blank rows above and below the
+/// letters are ignored.
+pub fn recognize(picture: &Grid<bool>) -> Result<String, OcrError> {
+    let lit_rows: Vec<usize> = picture
+        .rows()
+        .enumerate()
+        .filter(|(_, row)| row.contains(&true))
+        .map(|(y, _)| y)
+        .collect();
+    let (Some(&top), Some(&bottom)) = (lit_rows.first(), lit_rows.last()) else {
+        return Ok(String::new());
+    };
+
+    let height = bottom - top + 1;
+    let font = match height {
+        6 => font(&SMALL_FONT, SMALL_LETTERS),
+        10 => font(&LARGE_FONT, LARGE_LETTERS),
+        _ => return Err(OcrError::UnsupportedHeight(height)),
+    };
+
+    let rows: Vec<&[bool]> = picture.rows().skip(top).take(height).collect();
+    glyphs(&rows)
+        .map(|(column, glyph)| {
+            font.iter()
+                .find(|(_, known)| *known == glyph)
+                .map(|&(letter, _)| letter)
+                .ok_or(OcrError::UnknownGlyph { column, glyph })
+        })
+        .collect()
+}
+
+/// Reads the letters in a drawing made of `#` or `█` for lit pixels and `.` or spaces for dark
+/// ones. Lines may have different lengths, missing pixels are dark.
+pub fn recognize_str(text: &str) -> Result<String, OcrError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let width = lines.iter().map(|line| line.chars().count()).max();
+    let pixels: Vec<Vec<bool>> = lines
+        .iter()
+        .map(|line| {
+            line.chars()
+                .map(|ch| match ch {
+                    '#' | '█' => Ok(true),
+                    '.' | ' ' => Ok(false),
+                    _ => Err(OcrError::NotAPicture),
+                })
+                .collect()
+        })
+        .collect::<Result<_, _>>()?;
+    if !pixels.iter().flatten().any(|&lit| lit) {
+        return Err(OcrError::NotAPicture);
+    }
+
+    let picture = Grid::from_fn(width.unwrap_or(0), lines.len(), |(x, y)| {
+        pixels[y].get(x).copied().unwrap_or(false)
+    });
+    recognize(&picture)
+}
+
+/// Every glyph in `rows` as a `#`/`.` drawing, with the column where it starts. Glyphs are runs of
+/// columns with at least one lit pixel.
+fn glyphs<'a>(rows: &'a [&[bool]]) -> impl Iterator<Item = (usize, String)> + 'a {
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let lit = move |x: usize| rows.iter().any(|row| row.get(x) == Some(&true));
+
+    let mut x = 0;
+    std::iter::from_fn(move || {
+        let start = (x..width).find(|&x| lit(x))?;
+        let end = (start..width).find(|&x| !lit(x)).unwrap_or(width);
+        x = end;
+
+        let drawing: Vec<String> = rows
+            .iter()
+            .map(|row| {
+                (start..end)
+                    .map(|x| if row.get(x) == Some(&true) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        Some((start, drawing.join("\n")))
+    })
+}
+
+fn font(rows: &[&str], letters: &str) -> Vec<(char, String)> {
+    let pixels: Vec<Vec<bool>> = rows
+        .iter()
+        .map(|row| row.bytes().map(|b| b == b'#').collect())
+        .collect();
+    let rows: Vec<&[bool]> = pixels.iter().map(Vec::as_slice).collect();
+    letters
+        .chars()
+        .zip(glyphs(&rows).map(|(_, glyph)| glyph))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fonts_are_complete() {
+        assert_eq!(font(&SMALL_FONT, SMALL_LETTERS).len(), SMALL_LETTERS.len());
+        assert_eq!(font(&LARGE_FONT, LARGE_LETTERS).len(), LARGE_LETTERS.len());
+        // both fonts read themselves.
+        assert_eq!(
+            recognize_str(&SMALL_FONT.join("\n")).as_deref(),
+            Ok(SMALL_LETTERS)
+        );
+        assert_eq!(
+            recognize_str(&LARGE_FONT.join("\n")).as_deref(),
+            Ok(LARGE_LETTERS)
+        );
+    }
+
+    #[test]
+    fn reads_drawn_answers() {
+        // padded with blank rows and columns, and drawn with blocks and spaces.
+        let drawing = "
+ ██  ███  #..#
+█  █ █  █ #..#
+█  █ ███  ####
+████ █  █ #..#
+█  █ █  █ #..#
+█  █ ███  #..#
+
+";
+        assert_eq!(recognize_str(drawing).as_deref(), Ok("ABH"));
+
+        let grid = Grid::parse(&LARGE_FONT.join("\n"), |b| b == b'#').unwrap();
+        assert_eq!(recognize(&grid).as_deref(), Ok(LARGE_LETTERS));
+    }
+
+    #[test]
+    fn reports_what_it_cannot_read() {
+        assert_eq!(recognize_str("12\n34"), Err(OcrError::NotAPicture));
+        assert_eq!(recognize_str("...\n..."), Err(OcrError::NotAPicture));
+        assert_eq!(
+            recognize_str("#\n#\n#"),
+            Err(OcrError::UnsupportedHeight(3))
+        );
+
+        let drawing = ["####.#", "#..#.#", "#..#.#", "#..#.#", "#..#.#", "####.#"];
+        let err = recognize_str(&drawing.join("\n")).unwrap_err();
+        assert_eq!(
+            err,
+            OcrError::UnknownGlyph {
+                column: 0,
+                glyph: "####\n#..#\n#..#\n#..#\n#..#\n####".into()
+            }
+        );
+        assert!(
+            err.to_string()
+                .starts_with("unknown glyph at column 0:\n####")
+        );
+    }
+}
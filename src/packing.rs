@@ -0,0 +1,401 @@
+//! Exact packing of polyominoes into rectangular regions.
+//!
+//! Shapes may be rotated and reflected; each shape's distinct orientations are computed once and
+//! deduplicated. The search fills the region cell by cell in row-major order: the first undecided
+//! cell is either covered by a piece whose first cell lands on it, or deliberately left empty.
+//! Empty cells are paid for from the region's slack (free area minus piece area), which prunes
+//! the search as soon as the remaining pieces can no longer fit by area.
+//!
+//! Boards are bitsets, so after every step the search can cheaply mark cells that no remaining
+//! piece can cover anymore; those are forced empty and charged to the slack right away. States
+//! that failed once are remembered. Together with filling along the shorter side, which keeps the
+//! frontier narrow and the states few, this keeps proving that a region *can't* be packed
+//! tractable.
+use std::collections::HashSet;
+
+/// A set of cells, normalized so that its bounding box starts at `(0, 0)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Polyomino {
+    /// `(x, y)` cells sorted in row-major order.
+    cells: Vec<(usize, usize)>,
+    width: usize,
+    height: usize,
+}
+
+impl Polyomino {
+    pub fn from_cells(cells: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let mut cells: Vec<(usize, usize)> = cells.into_iter().collect();
+        let min_x = cells.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        for (x, y) in &mut cells {
+            (*x, *y) = (*x - min_x, *y - min_y);
+        }
+        cells.sort_unstable_by_key(|&(x, y)| (y, x));
+        cells.dedup();
+
+        Self {
+            width: cells.iter().map(|&(x, _)| x + 1).max().unwrap_or(0),
+            height: cells.iter().map(|&(_, y)| y + 1).max().unwrap_or(0),
+            cells,
+        }
+    }
+
+    /// Builds a shape from rows of cells, where `true` marks a filled cell.
+    pub fn from_grid<R: AsRef<[bool]>>(rows: &[R]) -> Self {
+        Self::from_cells(rows.iter().enumerate().flat_map(|(y, row)| {
+            row.as_ref()
+                .iter()
+                .enumerate()
+                .filter(|(_, filled)| **filled)
+                .map(move |(x, _)| (x, y))
+        }))
+    }
+
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The distinct shapes reachable by rotating and reflecting, in a canonical order.
+    pub fn orientations(&self) -> Vec<Polyomino> {
+        let extent = self.width.max(self.height) as isize;
+        let mut orientations: Vec<Polyomino> = (0..8)
+            .map(|symmetry| {
+                Self::from_cells(self.cells.iter().map(|&(x, y)| {
+                    let (x, y) = (x as isize, y as isize);
+                    let (x, y) = if symmetry & 4 != 0 { (-x, y) } else { (x, y) };
+                    let (x, y) = match symmetry & 3 {
+                        0 => (x, y),
+                        1 => (-y, x),
+                        2 => (-x, -y),
+                        _ => (y, -x),
+                    };
+                    // shift into the positive quadrant before normalizing.
+                    ((x + extent) as usize, (y + extent) as usize)
+                }))
+            })
+            .collect();
+        orientations.sort_unstable();
+        orientations.dedup();
+        orientations
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A piece placed by [`pack`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlacedPiece {
+    /// Index of the piece's shape in the shapes passed to [`pack`].
+    pub shape: usize,
+    /// `(x, y)` cells the piece covers in the region.
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// Whether `counts[i]` copies of every `shapes[i]` fit into a `width` x `height` region without
+/// overlapping. Shapes may be rotated and reflected, and there must be one count per shape.
+pub fn can_pack(width: usize, height: usize, shapes: &[Polyomino], counts: &[usize]) -> bool {
+    pack(width, height, shapes, counts).is_some()
+}
+
+/// Places `counts[i]` copies of every `shapes[i]` into a `width` x `height` region without
+/// overlapping, or returns `None` if they don't fit or there isn't one count per shape. Shapes may
+/// be rotated and reflected.
+pub fn pack(
+    width: usize,
+    height: usize,
+    shapes: &[Polyomino],
+    counts: &[usize],
+) -> Option<Vec<PlacedPiece>> {
+    if shapes.len() != counts.len() {
+        return None;
+    }
+
+    let piece_area: usize = shapes.iter().zip(counts).map(|(s, &c)| s.area() * c).sum();
+    let slack = (width * height).checked_sub(piece_area)?;
+
+    // shapes that aren't requested can be dropped, and empty shapes always fit.
+    let requested: Vec<usize> = (0..shapes.len())
+        .filter(|&idx| counts[idx] > 0 && shapes[idx].area() > 0)
+        .collect();
+    let empty_pieces = (0..shapes.len())
+        .filter(|&idx| shapes[idx].area() == 0)
+        .flat_map(|idx| {
+            (0..counts[idx]).map(move |_| PlacedPiece {
+                shape: idx,
+                cells: vec![],
+            })
+        });
+
+    // shapes can be rotated, so fill along the shorter side to keep the frontier narrow. The
+    // region is transposed back afterwards, which is just another reflection of every piece.
+    let transposed = width > height;
+    let (width, height) = (width.min(height), width.max(height));
+    let requested_shapes: Vec<&Polyomino> = requested.iter().map(|&idx| &shapes[idx]).collect();
+    let requested_counts = requested.iter().map(|&idx| counts[idx]).collect();
+    let mut packer = Packer::new(width, height, &requested_shapes, requested_counts);
+    let mut board = packer.outside.clone();
+    if !packer.search(&mut board, slack) {
+        return None;
+    }
+
+    let placed = packer.chosen.iter().map(|&(shape, idx)| {
+        let mask = &packer.placements[shape][idx].mask;
+        let cells = (0..width * height)
+            .filter(|&cell| mask[cell / 64] >> (cell % 64) & 1 == 1)
+            .map(|cell| (cell % width, cell / width))
+            .map(|(x, y)| if transposed { (y, x) } else { (x, y) })
+            .collect();
+        PlacedPiece {
+            shape: requested[shape],
+            cells,
+        }
+    });
+    Some(placed.chain(empty_pieces).collect())
+}
+
+/// A set of cells of the region, one bit per cell in row-major order.
+type Board = Vec<u64>;
+
+struct Placement {
+    mask: Board,
+    /// Row-major index of the first cell, which is where the search anchors the placement.
+    first: usize,
+}
+
+struct Packer {
+    /// Placements of every shape, in all orientations and positions.
+    placements: Vec<Vec<Placement>>,
+    remaining: Vec<usize>,
+    /// `(shape, placement)` of every piece on the board, in the order they were placed.
+    chosen: Vec<(usize, usize)>,
+    /// Padding bits past the last cell, which are always occupied.
+    outside: Board,
+    failed: HashSet<(Board, Vec<usize>)>,
+}
+
+impl Packer {
+    fn new(width: usize, height: usize, shapes: &[&Polyomino], counts: Vec<usize>) -> Self {
+        let num_cells = width * height;
+        let num_words = num_cells.div_ceil(64);
+
+        let placements = shapes
+            .iter()
+            .map(|shape| {
+                let mut placements = vec![];
+                for orientation in shape.orientations() {
+                    for oy in 0..(height + 1).saturating_sub(orientation.height) {
+                        for ox in 0..(width + 1).saturating_sub(orientation.width) {
+                            let mut mask = vec![0; num_words];
+                            for &(x, y) in orientation.cells() {
+                                let idx = (oy + y) * width + ox + x;
+                                mask[idx / 64] |= 1 << (idx % 64);
+                            }
+                            let (first_x, first_y) = orientation.cells()[0];
+                            let first = (oy + first_y) * width + ox + first_x;
+                            placements.push(Placement { mask, first });
+                        }
+                    }
+                }
+                placements.sort_by_key(|placement| placement.first);
+                placements
+            })
+            .collect();
+
+        let mut outside = vec![0; num_words];
+        for idx in num_cells..num_words * 64 {
+            outside[idx / 64] |= 1 << (idx % 64);
+        }
+
+        Self {
+            placements,
+            remaining: counts,
+            chosen: vec![],
+            outside,
+            failed: HashSet::new(),
+        }
+    }
+
+    /// `board` marks cells that are covered or decided to stay empty.
+    fn search(&mut self, board: &mut Board, slack: usize) -> bool {
+        if self.remaining.iter().all(|&count| count == 0) {
+            return true;
+        }
+
+        let key = (board.clone(), self.remaining.clone());
+        if self.failed.contains(&key) {
+            return false;
+        }
+
+        // cells no remaining piece can cover have to stay empty.
+        let dead = self.dead_cells(board);
+        let wasted: usize = dead.iter().map(|word| word.count_ones() as usize).sum();
+        let found = wasted <= slack && {
+            xor_into(board, &dead);
+            let found = self.branch(board, slack - wasted);
+            xor_into(board, &dead);
+            found
+        };
+
+        if !found {
+            self.failed.insert(key);
+        }
+        found
+    }
+
+    fn branch(&mut self, board: &mut Board, slack: usize) -> bool {
+        let Some(cell) = first_free(board) else {
+            return false;
+        };
+
+        for shape in 0..self.placements.len() {
+            if self.remaining[shape] == 0 {
+                continue;
+            }
+            let start = self.placements[shape].partition_point(|p| p.first < cell);
+            for idx in start..self.placements[shape].len() {
+                let placement = &self.placements[shape][idx];
+                if placement.first != cell {
+                    break;
+                }
+                if overlaps(board, &placement.mask) {
+                    continue;
+                }
+
+                let mask = placement.mask.clone();
+                xor_into(board, &mask);
+                self.remaining[shape] -= 1;
+                self.chosen.push((shape, idx));
+                if self.search(board, slack) {
+                    return true;
+                }
+                self.chosen.pop();
+                self.remaining[shape] += 1;
+                xor_into(board, &mask);
+            }
+        }
+
+        // leave the cell empty if the remaining pieces can afford it.
+        slack > 0 && {
+            board[cell / 64] |= 1 << (cell % 64);
+            let found = self.search(board, slack - 1);
+            board[cell / 64] &= !(1 << (cell % 64));
+            found
+        }
+    }
+
+    fn dead_cells(&self, board: &Board) -> Board {
+        let mut coverable = board.clone();
+        for (placements, &count) in self.placements.iter().zip(&self.remaining) {
+            if count == 0 {
+                continue;
+            }
+            for placement in placements.iter().filter(|p| !overlaps(board, &p.mask)) {
+                for (word, mask) in coverable.iter_mut().zip(&placement.mask) {
+                    *word |= mask;
+                }
+            }
+        }
+        coverable.iter().map(|word| !word).collect()
+    }
+}
+
+fn first_free(board: &Board) -> Option<usize> {
+    board
+        .iter()
+        .enumerate()
+        .find(|(_, word)| **word != u64::MAX)
+        .map(|(w, word)| w * 64 + word.trailing_ones() as usize)
+}
+
+fn overlaps(board: &Board, mask: &Board) -> bool {
+    board.iter().zip(mask).any(|(a, b)| a & b != 0)
+}
+
+fn xor_into(board: &mut Board, mask: &Board) {
+    for (word, mask) in board.iter_mut().zip(mask) {
+        *word ^= mask;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(rows: &[&str]) -> Polyomino {
+        let grid: Vec<Vec<bool>> = rows
+            .iter()
+            .map(|row| row.bytes().map(|b| b == b'#').collect())
+            .collect();
+        Polyomino::from_grid(&grid)
+    }
+
+    #[test]
+    fn canonicalizes_orientations() {
+        assert_eq!(shape(&["##", "##"]).orientations().len(), 1);
+        assert_eq!(shape(&["###", ".#."]).orientations().len(), 4);
+        assert_eq!(shape(&["##.", ".##"]).orientations().len(), 4);
+        assert_eq!(shape(&["#..", "###"]).orientations().len(), 8);
+
+        let rotated = shape(&["#.", "##", "#."]);
+        assert!(shape(&["###", ".#."]).orientations().contains(&rotated));
+    }
+
+    #[test]
+    fn packs_by_geometry_not_just_area() {
+        let t = shape(&["###", ".#."]);
+        let l = shape(&["#..", "###"]);
+
+        // two T pieces have the right area for a 4x2 box but can't tile it.
+        assert!(!can_pack(4, 2, std::slice::from_ref(&t), &[2]));
+        assert!(can_pack(4, 3, std::slice::from_ref(&t), &[2]));
+        // four L pieces tile a 4x4 square exactly.
+        assert!(can_pack(4, 4, std::slice::from_ref(&l), &[4]));
+        // but swapping two of them for T pieces doesn't, even though the area still matches.
+        assert!(!can_pack(4, 4, &[t.clone(), l.clone()], &[2, 2]));
+        assert!(can_pack(3, 3, &[t, l], &[1, 1]));
+    }
+
+    #[test]
+    fn handles_degenerate_requests() {
+        let domino = shape(&["##"]);
+        assert!(can_pack(0, 0, std::slice::from_ref(&domino), &[0]));
+        assert!(!can_pack(1, 1, std::slice::from_ref(&domino), &[1]));
+        assert!(can_pack(1, 2, std::slice::from_ref(&domino), &[1]));
+        // counts that don't match the shapes.
+        assert!(!can_pack(2, 2, std::slice::from_ref(&domino), &[1, 1]));
+        assert!(!can_pack(2, 2, &[domino.clone(), domino], &[1]));
+    }
+
+    #[test]
+    fn returns_placements_in_region_coordinates() {
+        let t = shape(&["###", ".#."]);
+        let domino = shape(&["##"]);
+        // wider than tall, so the packer works on the transposed region.
+        let shapes = [domino, Polyomino::from_cells([]), t.clone()];
+        let placed = pack(5, 3, &shapes, &[1, 1, 2]).unwrap();
+
+        let mut counts = [0; 3];
+        let mut covered = HashSet::new();
+        for piece in &placed {
+            counts[piece.shape] += 1;
+            assert!(piece.cells.iter().all(|&(x, y)| x < 5 && y < 3));
+            assert!(piece.cells.iter().all(|&cell| covered.insert(cell)));
+            let normalized = Polyomino::from_cells(piece.cells.iter().copied());
+            assert!(shapes[piece.shape].orientations().contains(&normalized));
+        }
+        assert_eq!(counts, [1, 1, 2]);
+        assert_eq!(pack(4, 2, std::slice::from_ref(&t), &[2]), None);
+    }
+}
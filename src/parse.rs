@@ -0,0 +1,456 @@
+//! Declarative helpers for the usual puzzle input formats.
+//!
+//! Failures are reported as a [`ParseError`] pointing at the offending line and column instead
+//! of panicking somewhere inside a solution.
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::num::Int;
+
+/// A parse failure with its 1-based position in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line of the failure, or `0` if the parsed text was not part of a line-based input.
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line: 0,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Attaches a line number to an error produced while parsing a single line.
+    #[must_use]
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = line;
+        self
+    }
+
+    /// Shifts the column of an error produced while parsing a substring starting at `offset`.
+    #[must_use]
+    pub fn offset_by(mut self, offset: usize) -> Self {
+        self.column += offset;
+        self
+    }
+}
+
+impl Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line > 0 {
+            write!(
+                f,
+                "line {}, column {}: {}",
+                self.line, self.column, self.message
+            )
+        } else {
+            write!(f, "column {}: {}", self.column, self.message)
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Byte offset of `inner` within `outer`, for shifting errors from a sub-slice with
+/// [`ParseError::offset_by`]. `inner` must be a slice of `outer`.
+pub fn offset_of(outer: &str, inner: &str) -> usize {
+    let offset = (inner.as_ptr() as usize).wrapping_sub(outer.as_ptr() as usize);
+    assert!(offset <= outer.len(), "`inner` is not a slice of `outer`");
+    offset
+}
+
+/// Parses every line of `input` with `f`, attaching the line number to the first failure.
+pub fn parse_lines<'a, T>(
+    input: &'a str,
+    mut f: impl FnMut(&'a str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| f(line).map_err(|err| err.at_line(i + 1)))
+        .collect()
+}
+
+/// Iterates the blank-line separated sections of `input` without copying it.
+///
+/// Both `\n` and `\r\n` line endings are accepted, and runs of several blank lines do not produce
+/// empty sections.
+pub fn sections(input: &str) -> Sections<'_> {
+    Sections { rest: input }
+}
+
+/// Iterator returned by [`sections`].
+pub struct Sections<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Sections<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut start = None;
+        let mut end = 0;
+        let mut offset = 0;
+
+        for line in self.rest.split_inclusive('\n') {
+            let content = line.trim_end_matches(['\n', '\r']);
+            if content.trim().is_empty() {
+                if start.is_some() {
+                    break;
+                }
+            } else {
+                start.get_or_insert(offset);
+                end = offset + content.len();
+            }
+            offset += line.len();
+        }
+
+        let section = &self.rest[start?..end];
+        self.rest = &self.rest[offset..];
+        Some(section)
+    }
+}
+
+/// Parses a comma separated list such as `3,5,4,7`, ignoring whitespace around items.
+pub fn comma_list<'a, T: FromCapture<'a>>(s: &'a str) -> Result<Vec<T>, ParseError> {
+    let mut offset = 0;
+    s.split(',')
+        .map(|item| {
+            let column = offset + item.len() - item.trim_start().len() + 1;
+            offset += item.len() + 1;
+            T::from_capture(item.trim()).map_err(|message| ParseError::new(column, message))
+        })
+        .collect()
+}
+
+/// Extracts every integer in `line`, treating a `-` directly in front of a digit as a sign.
+pub fn ints<T: Int>(line: &str) -> Result<Vec<T>, ParseError> {
+    extract_numbers(line, true)
+}
+
+/// Extracts every run of digits in `line`, ignoring signs (e.g. for ranges like `3-5`).
+pub fn uints<T: Int>(line: &str) -> Result<Vec<T>, ParseError> {
+    extract_numbers(line, false)
+}
+
+fn extract_numbers<T: Int>(line: &str, signed: bool) -> Result<Vec<T>, ParseError> {
+    let bytes = line.as_bytes();
+    let mut numbers = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let is_signed_start =
+            signed && bytes[i] == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        if !bytes[i].is_ascii_digit() && !is_signed_start {
+            i += 1;
+            continue;
+        }
+        let (value, len) = if signed {
+            int_prefix(&bytes[i..])
+        } else {
+            uint_prefix(&bytes[i..])
+        }
+        .ok_or_else(|| ParseError::new(i + 1, "integer out of range"))?;
+        numbers.push(value);
+        i += len;
+    }
+    Ok(numbers)
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Parses the unsigned number at the start of `bytes`, returning it with the number of bytes
+/// consumed. Returns `None` if `bytes` does not start with a digit or the value overflows `T`.
+#[inline]
+pub fn uint_prefix<T: Int>(bytes: &[u8]) -> Option<(T, usize)> {
+    let len = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+    if len == 0 {
+        return None;
+    }
+    let value = bytes[..len].iter().try_fold(T::ZERO, |acc, &b| {
+        acc.checked_mul(T::TEN)?
+            .checked_add(T::from_i128(i128::from(b - b'0'))?)
+    })?;
+    Some((value, len))
+}
+
+/// Like [`uint_prefix`], but accepts a leading `-`.
+#[inline]
+pub fn int_prefix<T: Int>(bytes: &[u8]) -> Option<(T, usize)> {
+    match bytes.first() {
+        Some(b'-') => {
+            let len = bytes[1..].iter().take_while(|b| b.is_ascii_digit()).count();
+            if len == 0 {
+                return None;
+            }
+            let value = bytes[1..=len].iter().try_fold(T::ZERO, |acc, &b| {
+                acc.checked_mul(T::TEN)?
+                    .checked_sub(T::from_i128(i128::from(b - b'0'))?)
+            })?;
+            Some((value, len + 1))
+        }
+        _ => uint_prefix(bytes),
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Types that can be produced from a `{}` capture of [`scan!`](crate::scan) or a list item.
+pub trait FromCapture<'a>: Sized {
+    fn from_capture(s: &'a str) -> Result<Self, String>;
+}
+
+impl<'a> FromCapture<'a> for &'a str {
+    fn from_capture(s: &'a str) -> Result<Self, String> {
+        Ok(s)
+    }
+}
+
+macro_rules! impl_from_capture {
+    ($($t:ty),*) => {
+        $(
+            impl FromCapture<'_> for $t {
+                fn from_capture(s: &str) -> Result<Self, String> {
+                    s.parse()
+                        .map_err(|err| format!("cannot parse `{s}` as {}: {err}", stringify!($t)))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_capture!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, char, bool, String
+);
+
+/// A piece of a [`scan!`](crate::scan) pattern.
+enum Token {
+    Literal(String),
+    Capture,
+}
+
+fn tokenize(pattern: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, chars.peek()) {
+            ('{', Some('}')) => {
+                chars.next();
+                if !literal.is_empty() {
+                    tokens.push(Token::Literal(std::mem::take(&mut literal)));
+                }
+                tokens.push(Token::Capture);
+            }
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                literal.push(ch);
+            }
+            _ => literal.push(ch),
+        }
+    }
+    if !literal.is_empty() {
+        tokens.push(Token::Literal(literal));
+    }
+    tokens
+}
+
+/// Counts the `{}` placeholders of a [`scan!`](crate::scan) pattern, honouring `{{` and `}}`.
+pub const fn count_captures(pattern: &str) -> usize {
+    let bytes = pattern.as_bytes();
+    let mut count = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if i + 1 < bytes.len() && bytes[i] == b'{' && bytes[i + 1] == b'}' {
+            count += 1;
+            i += 2;
+        } else if i + 1 < bytes.len()
+            && (bytes[i] == b'{' && bytes[i + 1] == b'{'
+                || bytes[i] == b'}' && bytes[i + 1] == b'}')
+        {
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    count
+}
+
+/// Whether two `{}` placeholders of a [`scan!`](crate::scan) pattern follow each other without a
+/// literal between them, which leaves the end of the first capture ambiguous.
+pub const fn has_adjacent_captures(pattern: &str) -> bool {
+    let bytes = pattern.as_bytes();
+    let mut previous_was_capture = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        if i + 1 < bytes.len() && bytes[i] == b'{' && bytes[i + 1] == b'}' {
+            if previous_was_capture {
+                return true;
+            }
+            previous_was_capture = true;
+            i += 2;
+        } else if i + 1 < bytes.len()
+            && (bytes[i] == b'{' && bytes[i + 1] == b'{'
+                || bytes[i] == b'}' && bytes[i + 1] == b'}')
+        {
+            previous_was_capture = false;
+            i += 2;
+        } else {
+            previous_was_capture = false;
+            i += 1;
+        }
+    }
+    false
+}
+
+/// Matches `input` against `pattern` and returns the byte offset and text of every capture.
+///
+/// A capture extends up to the first occurrence of the literal that follows it, or to the end of
+/// the input if it is the last token of the pattern. Two captures without a literal between them
+/// are ambiguous and rejected.
+pub fn scan_captures<'a>(
+    input: &'a str,
+    pattern: &str,
+) -> Result<Vec<(usize, &'a str)>, ParseError> {
+    let tokens = tokenize(pattern);
+    let mut captures = vec![];
+    let mut pos = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Literal(literal) => {
+                if !input[pos..].starts_with(literal.as_str()) {
+                    return Err(ParseError::new(pos + 1, format!("expected `{literal}`")));
+                }
+                pos += literal.len();
+            }
+            Token::Capture => {
+                let end = match tokens.get(i + 1) {
+                    None => input.len(),
+                    Some(Token::Literal(next)) => {
+                        pos + input[pos..].find(next.as_str()).ok_or_else(|| {
+                            ParseError::new(pos + 1, format!("expected `{next}` after value"))
+                        })?
+                    }
+                    Some(Token::Capture) => {
+                        let message = "adjacent `{}` placeholders are ambiguous";
+                        return Err(ParseError::new(pos + 1, message));
+                    }
+                };
+                captures.push((pos, &input[pos..end]));
+                pos = end;
+            }
+        }
+    }
+
+    if pos != input.len() {
+        return Err(ParseError::new(pos + 1, "unexpected trailing input"));
+    }
+    Ok(captures)
+}
+
+/// Converts one capture returned by [`scan_captures`], reporting failures at its column.
+pub fn parse_capture<'a, T: FromCapture<'a>>(
+    (offset, text): (usize, &'a str),
+) -> Result<T, ParseError> {
+    T::from_capture(text).map_err(|message| ParseError::new(offset + 1, message))
+}
+
+/// Matches a string against a pattern with `{}` placeholders and parses each capture.
+///
+/// Literal braces are written as `{{` and `}}`. The number of placeholders is checked against the
+/// number of types at compile time, as is that every placeholder is followed by a literal or the
+/// end of the pattern.
+#[macro_export]
+macro_rules! scan {
+    ($input:expr, $pattern:literal => $($t:ty),+ $(,)?) => {{
+        const _: () = assert!(
+            $crate::parse::count_captures($pattern) == 0 $(+ { stringify!($t); 1 })+,
+            "number of placeholders does not match the number of types"
+        );
+        const _: () = assert!(
+            !$crate::parse::has_adjacent_captures($pattern),
+            "adjacent `{{}}` placeholders are ambiguous"
+        );
+        let input: &str = $input;
+        $crate::parse::scan_captures(input, $pattern).and_then(|captures| {
+            let mut captures = captures.into_iter();
+            Ok(($($crate::parse::parse_capture::<$t>(captures.next().unwrap())?,)+))
+        })
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_patterns() {
+        let (w, h, quantities) = scan!("12x5: 1 0 1 0 2 2", "{}x{}: {}" => u32, u32, &str).unwrap();
+        assert_eq!((w, h, quantities), (12, 5, "1 0 1 0 2 2"));
+
+        let (lights, buttons, joltage) =
+            scan!("[.##.] (3) (1,3) {3,5,4,7}", "[{}] {} {{{}}}" => &str, &str, &str).unwrap();
+        assert_eq!((lights, buttons, joltage), (".##.", "(3) (1,3)", "3,5,4,7"));
+    }
+
+    #[test]
+    fn reports_positions() {
+        let err = scan!("12y5: 1", "{}x{}: {}" => u32, u32, &str).unwrap_err();
+        assert_eq!(err, ParseError::new(1, "expected `x` after value"));
+
+        let err = scan!("12x5! 1", "{}x{}: {}" => u32, u32, &str).unwrap_err();
+        assert_eq!(err.column, 4);
+
+        let err = scan!("12xfive: 1", "{}x{}: {}" => u32, u32, &str).unwrap_err();
+        assert_eq!(err.column, 4);
+        assert!(err.message.contains("`five`"));
+
+        let err = parse_lines("1,2\n3,x\n", comma_list::<u8>).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 3: cannot parse `x` as u8: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn finds_adjacent_captures() {
+        assert!(has_adjacent_captures("{}{}"));
+        assert!(has_adjacent_captures("a {}{} b"));
+        assert!(!has_adjacent_captures("{}x{}: {}"));
+        assert!(!has_adjacent_captures("[{}] {} {{{}}}"));
+        assert!(!has_adjacent_captures("{}{{}}{}"));
+
+        let err = scan_captures("x12", "x{}{}").unwrap_err();
+        assert_eq!(err.column, 2);
+        assert!(err.message.contains("ambiguous"));
+    }
+
+    #[test]
+    fn splits_sections() {
+        let input = "a\nb\n\nc\r\n\r\n\r\nd\r\ne\r\n";
+        assert_eq!(sections(input).collect::<Vec<_>>(), ["a\nb", "c", "d\r\ne"]);
+        assert_eq!(sections("\n\nx").collect::<Vec<_>>(), ["x"]);
+        assert_eq!(sections("").next(), None);
+    }
+
+    #[test]
+    fn extracts_numbers() {
+        assert_eq!(comma_list::<u64>("3, 5,4 ,7").unwrap(), [3, 5, 4, 7]);
+        assert_eq!(ints::<i64>("p=-3,12 v=4,-5").unwrap(), [-3, 12, 4, -5]);
+        assert_eq!(uints::<u64>("11-22,95-115").unwrap(), [11, 22, 95, 115]);
+        assert_eq!(ints::<u8>("x=300").unwrap_err().column, 3);
+        assert_eq!(uint_prefix::<u64>(b"1234abc"), Some((1234, 4)));
+        assert_eq!(uint_prefix::<u64>(b"abc"), None);
+        assert_eq!(uint_prefix::<u8>(b"256"), None);
+        assert_eq!(int_prefix::<i8>(b"-128,"), Some((-128, 4)));
+        assert_eq!(int_prefix::<i64>(b"-"), None);
+    }
+}
@@ -0,0 +1,861 @@
+//! Pictures of visualization frames, and encoders that turn them into shareable files.
+//!
+//! A picture is either a raster of coloured cells or a scene of vector shapes in puzzle
+//! coordinates. Recordings are written as an animated GIF, an animated PNG, or an SVG of the last
+//! frame. The encoders are small enough to keep in the template instead of pulling in image crates:
+//! GIF data is LZW-compressed, and PNG data uses a fixed-Huffman deflate that mostly refers back to
+//! the previous row, which is all that upscaled cells need.
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::grid::Grid;
+use crate::viz::Colour;
+
+/// Longest side of recorded images, in pixels.
+const TARGET_SIZE: usize = 640;
+const DOT_RADIUS: f64 = 3.0;
+const LINE_WIDTH: f64 = 1.5;
+
+const BACKGROUND: [u8; 3] = [0x0f, 0x0f, 0x23];
+/// Background followed by every [`Colour`], padded to a power of two for GIF.
+const PALETTE_SIZE: usize = 16;
+
+impl Colour {
+    const ALL: [Colour; 8] = [
+        Colour::Red,
+        Colour::Green,
+        Colour::Yellow,
+        Colour::Blue,
+        Colour::Magenta,
+        Colour::Cyan,
+        Colour::White,
+        Colour::Grey,
+    ];
+
+    fn rgb(self) -> [u8; 3] {
+        match self {
+            Colour::Red => [0xe0, 0x4f, 0x5f],
+            Colour::Green => [0x00, 0xcc, 0x66],
+            Colour::Yellow => [0xff, 0xd7, 0x00],
+            Colour::Blue => [0x4f, 0x8f, 0xff],
+            Colour::Magenta => [0xc6, 0x78, 0xdd],
+            Colour::Cyan => [0x56, 0xd8, 0xe4],
+            Colour::White => [0xf0, 0xf0, 0xf0],
+            Colour::Grey => [0x5c, 0x63, 0x70],
+        }
+    }
+
+    fn palette_index(self) -> u8 {
+        Colour::ALL.iter().position(|&c| c == self).unwrap() as u8 + 1
+    }
+
+    fn hex(self) -> String {
+        let [r, g, b] = self.rgb();
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+fn palette() -> [[u8; 3]; PALETTE_SIZE] {
+    let mut palette = [BACKGROUND; PALETTE_SIZE];
+    for colour in Colour::ALL {
+        palette[colour.palette_index() as usize] = colour.rgb();
+    }
+    palette
+}
+
+/// A grid of cells, each either coloured or showing the background.
+pub type Raster = Grid<Option<Colour>>;
+
+/// Palette indices with every cell blown up to `scale` x `scale` pixels.
+fn upscale(raster: &Raster, scale: usize) -> Image {
+    let mut image = Image::new(raster.width() * scale, raster.height() * scale);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let colour = raster[(x / scale, y / scale)];
+            image.pixels[y * image.width + x] = colour.map_or(0, Colour::palette_index);
+        }
+    }
+    image
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// A filled polygon with its corners in order.
+    Polygon {
+        points: Vec<(f64, f64)>,
+        fill: Colour,
+    },
+    /// A straight line of fixed width, however far the scene is zoomed.
+    Line {
+        from: (f64, f64),
+        to: (f64, f64),
+        colour: Colour,
+    },
+    /// A point marker of fixed size.
+    Dot { centre: (f64, f64), colour: Colour },
+}
+
+/// Vector shapes in puzzle coordinates, with `y` growing downwards. Later shapes are drawn on top.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scene {
+    min: (f64, f64),
+    max: (f64, f64),
+    pub shapes: Vec<Shape>,
+}
+
+impl Scene {
+    /// An empty scene showing the rectangle from `min` to `max`.
+    pub fn new(min: (f64, f64), max: (f64, f64)) -> Self {
+        Self {
+            min,
+            max,
+            shapes: vec![],
+        }
+    }
+
+    /// The smallest scene that shows all `points`, with a margin around them.
+    pub fn around(points: impl IntoIterator<Item = (f64, f64)>) -> Self {
+        let (mut min, mut max) = ((f64::MAX, f64::MAX), (f64::MIN, f64::MIN));
+        for (x, y) in points {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        if min.0 > max.0 {
+            return Self::new((0.0, 0.0), (1.0, 1.0));
+        }
+        let margin = 0.05 * (max.0 - min.0).max(max.1 - min.1).max(1.0);
+        Self::new(
+            (min.0 - margin, min.1 - margin),
+            (max.0 + margin, max.1 + margin),
+        )
+    }
+
+    pub fn push(&mut self, shape: Shape) {
+        self.shapes.push(shape);
+    }
+
+    /// Width and height of the shown rectangle, in puzzle units.
+    pub fn size(&self) -> (f64, f64) {
+        (
+            (self.max.0 - self.min.0).max(f64::EPSILON),
+            (self.max.1 - self.min.1).max(f64::EPSILON),
+        )
+    }
+
+    /// Renders the scene into `width` x `height` cells. Dots and lines keep the given size in
+    /// cells, so that they stay visible at any zoom.
+    pub fn rasterize(
+        &self,
+        width: usize,
+        height: usize,
+        dot_radius: f64,
+        line_width: f64,
+    ) -> Raster {
+        let mut raster = Raster::new(width, height, None);
+        let (extent_x, extent_y) = self.size();
+        let (scale_x, scale_y) = (width as f64 / extent_x, height as f64 / extent_y);
+        let to_pixel =
+            |(x, y): (f64, f64)| ((x - self.min.0) * scale_x, (y - self.min.1) * scale_y);
+
+        for shape in &self.shapes {
+            match shape {
+                Shape::Polygon { points, fill } => {
+                    let points: Vec<_> = points.iter().map(|&p| to_pixel(p)).collect();
+                    fill_polygon(&mut raster, &points, *fill);
+                }
+                Shape::Line { from, to, colour } => {
+                    let (from, to) = (to_pixel(*from), to_pixel(*to));
+                    let length = (to.0 - from.0).hypot(to.1 - from.1);
+                    let steps = (2.0 * length).ceil().max(1.0) as usize;
+                    for step in 0..=steps {
+                        let t = step as f64 / steps as f64;
+                        let point = (from.0 + t * (to.0 - from.0), from.1 + t * (to.1 - from.1));
+                        fill_disc(&mut raster, point, line_width / 2.0, *colour);
+                    }
+                }
+                Shape::Dot { centre, colour } => {
+                    fill_disc(&mut raster, to_pixel(*centre), dot_radius, *colour);
+                }
+            }
+        }
+        raster
+    }
+
+    /// Size in pixels of a recorded image of the scene.
+    fn image_size(&self) -> (usize, usize) {
+        let (extent_x, extent_y) = self.size();
+        let scale = TARGET_SIZE as f64 / extent_x.max(extent_y);
+        (
+            ((extent_x * scale).round() as usize).max(1),
+            ((extent_y * scale).round() as usize).max(1),
+        )
+    }
+}
+
+fn paint(raster: &mut Raster, pos: (usize, usize), colour: Colour) {
+    if let Some(cell) = raster.get_mut(pos) {
+        *cell = Some(colour);
+    }
+}
+
+/// Even-odd fill, sampling every cell at its centre.
+fn fill_polygon(raster: &mut Raster, points: &[(f64, f64)], colour: Colour) {
+    let mut crossings = vec![];
+    for y in 0..raster.height() {
+        let sample_y = y as f64 + 0.5;
+        crossings.clear();
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            if (y0 <= sample_y) != (y1 <= sample_y) {
+                crossings.push(x0 + (sample_y - y0) / (y1 - y0) * (x1 - x0));
+            }
+        }
+        crossings.sort_unstable_by(f64::total_cmp);
+        for span in crossings.chunks_exact(2) {
+            let start = (span[0] - 0.5).ceil().max(0.0) as usize;
+            let end = ((span[1] - 0.5).floor() + 1.0).clamp(0.0, raster.width() as f64) as usize;
+            for x in start..end {
+                paint(raster, (x, y), colour);
+            }
+        }
+    }
+}
+
+fn fill_disc(raster: &mut Raster, (cx, cy): (f64, f64), radius: f64, colour: Colour) {
+    let radius = radius.max(0.5);
+    let (x0, x1) = ((cx - radius).floor().max(0.0), (cx + radius).ceil());
+    let (y0, y1) = ((cy - radius).floor().max(0.0), (cy + radius).ceil());
+    for y in y0 as usize..y1 as usize {
+        for x in x0 as usize..x1 as usize {
+            let (dx, dy) = (x as f64 + 0.5 - cx, y as f64 + 0.5 - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                paint(raster, (x, y), colour);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Picture {
+    Raster(Raster),
+    Scene(Scene),
+}
+
+impl Picture {
+    /// Palette indices at recording size.
+    fn to_image(&self) -> Image {
+        match self {
+            Picture::Raster(raster) => {
+                let side = raster.width().max(raster.height()).max(1);
+                upscale(raster, (TARGET_SIZE / side).max(1))
+            }
+            Picture::Scene(scene) => {
+                let (width, height) = scene.image_size();
+                upscale(&scene.rasterize(width, height, DOT_RADIUS, LINE_WIDTH), 1)
+            }
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Gif,
+    Apng,
+    Svg,
+}
+
+impl Format {
+    /// Picks the format from a file extension: `.gif`, `.png` or `.apng`, or `.svg`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "gif" => Some(Format::Gif),
+            "png" | "apng" => Some(Format::Apng),
+            "svg" => Some(Format::Svg),
+            _ => None,
+        }
+    }
+}
+
+/// Encodes a recording, showing `fps` frames per second. An SVG only shows the last picture.
+pub fn encode(format: Format, pictures: &[Picture], fps: u32) -> Vec<u8> {
+    match format {
+        Format::Svg => pictures.last().map(svg).unwrap_or_default().into_bytes(),
+        Format::Gif | Format::Apng => {
+            let images = pictures.iter().map(Picture::to_image);
+            let frames = Image::on_common_canvas(images.collect());
+            if format == Format::Gif {
+                gif(&frames, fps)
+            } else {
+                apng(&frames, fps)
+            }
+        }
+    }
+}
+
+/// Pixels as palette indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    /// Animations have one size, so smaller frames are padded with background.
+    fn on_common_canvas(images: Vec<Image>) -> Vec<Image> {
+        let width = images.iter().map(|image| image.width).max().unwrap_or(1);
+        let height = images.iter().map(|image| image.height).max().unwrap_or(1);
+        images
+            .into_iter()
+            .map(|image| {
+                if (image.width, image.height) == (width, height) {
+                    return image;
+                }
+                let mut canvas = Image::new(width, height);
+                for (y, row) in image.pixels.chunks(image.width.max(1)).enumerate() {
+                    canvas.pixels[y * width..y * width + row.len()].copy_from_slice(row);
+                }
+                canvas
+            })
+            .collect()
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+fn gif(frames: &[Image], fps: u32) -> Vec<u8> {
+    const MIN_CODE_SIZE: u8 = 4;
+    let (width, height) = frames.first().map_or((1, 1), |f| (f.width, f.height));
+    let delay = (100 / fps.max(1)).max(2) as u16;
+
+    let mut out = b"GIF89a".to_vec();
+    out.extend((width as u16).to_le_bytes());
+    out.extend((height as u16).to_le_bytes());
+    // global colour table of 2^(3 + 1) entries, 8 bits per channel.
+    out.extend([0xf3, 0, 0]);
+    out.extend(palette().into_iter().flatten());
+    // loop forever.
+    out.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+
+    for frame in frames {
+        out.extend([0x21, 0xf9, 0x04, 0x00]);
+        out.extend(delay.to_le_bytes());
+        out.extend([0x00, 0x00]);
+
+        out.extend([0x2c, 0, 0, 0, 0]);
+        out.extend((width as u16).to_le_bytes());
+        out.extend((height as u16).to_le_bytes());
+        out.push(0);
+
+        out.push(MIN_CODE_SIZE);
+        for block in lzw(&frame.pixels, MIN_CODE_SIZE).chunks(255) {
+            out.push(block.len() as u8);
+            out.extend(block);
+        }
+        out.push(0);
+    }
+
+    out.push(0x3b);
+    out
+}
+
+/// Variable-width LZW as used by GIF, starting with a clear code and resetting once all 4096 codes
+/// are taken.
+fn lzw(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    const MAX_CODES: u16 = 4096;
+    let clear = 1_u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut bits = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end + 1;
+    let mut code_size = min_code_size as u32 + 1;
+
+    // the decoder adds table entries one code behind the encoder, so codes only widen once the
+    // entry for the code just written no longer fits.
+    fn emit(bits: &mut BitWriter, code: u16, next_code: u16, code_size: &mut u32) {
+        bits.put(code as u32, *code_size);
+        if next_code >= 1 << *code_size && *code_size < 12 {
+            *code_size += 1;
+        }
+    }
+
+    bits.put(clear as u32, code_size);
+    let mut prefix: Option<u16> = None;
+    for &index in indices {
+        let Some(current) = prefix else {
+            prefix = Some(index as u16);
+            continue;
+        };
+        if let Some(&code) = table.get(&(current, index)) {
+            prefix = Some(code);
+            continue;
+        }
+
+        emit(&mut bits, current, next_code, &mut code_size);
+        if next_code < MAX_CODES {
+            table.insert((current, index), next_code);
+            next_code += 1;
+        } else {
+            bits.put(clear as u32, code_size);
+            table.clear();
+            next_code = end + 1;
+            code_size = min_code_size as u32 + 1;
+        }
+        prefix = Some(index as u16);
+    }
+
+    if let Some(current) = prefix {
+        emit(&mut bits, current, next_code, &mut code_size);
+    }
+    emit(&mut bits, end, next_code, &mut code_size);
+    bits.finish()
+}
+
+/// Packs bit fields starting at the least significant bit of every byte.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u64,
+    len: u32,
+}
+
+impl BitWriter {
+    fn put(&mut self, value: u32, num_bits: u32) {
+        self.current |= (value as u64 & ((1 << num_bits) - 1)) << self.len;
+        self.len += num_bits;
+        while self.len >= 8 {
+            self.bytes.push(self.current as u8);
+            self.current >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which deflate stores starting with its most significant bit.
+    fn put_code(&mut self, code: u32, num_bits: u32) {
+        self.put(code.reverse_bits() >> (32 - num_bits), num_bits);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.current as u8);
+        }
+        self.bytes
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+fn apng(frames: &[Image], fps: u32) -> Vec<u8> {
+    let (width, height) = frames.first().map_or((1, 1), |f| (f.width, f.height));
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    let mut header = vec![];
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8-bit palette indices, default compression, filtering and no interlacing.
+    header.extend([8, 3, 0, 0, 0]);
+    png_chunk(&mut out, b"IHDR", &header);
+    png_chunk(&mut out, b"PLTE", &palette().concat());
+
+    let mut animation = vec![];
+    animation.extend((frames.len() as u32).to_be_bytes());
+    animation.extend(0_u32.to_be_bytes());
+    png_chunk(&mut out, b"acTL", &animation);
+
+    let mut sequence = 0_u32;
+    for (idx, frame) in frames.iter().enumerate() {
+        let mut control = sequence.to_be_bytes().to_vec();
+        control.extend((width as u32).to_be_bytes());
+        control.extend((height as u32).to_be_bytes());
+        control.extend([0; 8]);
+        control.extend(1_u16.to_be_bytes());
+        control.extend((fps.max(1) as u16).to_be_bytes());
+        control.extend([0, 0]);
+        png_chunk(&mut out, b"fcTL", &control);
+        sequence += 1;
+
+        // every row starts with filter type 0, i.e. no filtering.
+        let mut raw = Vec::with_capacity((width + 1) * height);
+        for row in frame.pixels.chunks(width.max(1)) {
+            raw.push(0);
+            raw.extend(row);
+        }
+        let data = zlib(&raw, width + 1);
+
+        if idx == 0 {
+            png_chunk(&mut out, b"IDAT", &data);
+        } else {
+            let mut frame_data = sequence.to_be_bytes().to_vec();
+            frame_data.extend(data);
+            png_chunk(&mut out, b"fdAT", &frame_data);
+            sequence += 1;
+        }
+    }
+
+    png_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// A zlib stream holding one fixed-Huffman deflate block. Matches are only looked for one byte
+/// back and one `stride` back, which covers runs within a row and rows repeated from upscaling.
+fn zlib(data: &[u8], stride: usize) -> Vec<u8> {
+    const LENGTH_BASE: [u16; 29] = [
+        3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+        131, 163, 195, 227, 258,
+    ];
+    const LENGTH_EXTRA: [u32; 29] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+    ];
+    const DISTANCE_BASE: [u16; 30] = [
+        1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+        2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+    ];
+    const DISTANCE_EXTRA: [u32; 30] = [
+        0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12,
+        13, 13,
+    ];
+    const MAX_MATCH: usize = 258;
+    const MAX_DISTANCE: usize = 32768;
+
+    fn put_symbol(bits: &mut BitWriter, symbol: u16) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => bits.put_code(0x30 + symbol, 8),
+            144..=255 => bits.put_code(0x190 + symbol - 144, 9),
+            256..=279 => bits.put_code(symbol - 256, 7),
+            _ => bits.put_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    let mut bits = BitWriter::default();
+    // final block, fixed Huffman codes.
+    bits.put(1, 1);
+    bits.put(1, 2);
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let limit = MAX_MATCH.min(data.len() - pos);
+        let best = [1, stride]
+            .into_iter()
+            .filter(|&distance| distance <= pos && distance <= MAX_DISTANCE)
+            .map(|distance| {
+                let length = (0..limit)
+                    .take_while(|&k| data[pos + k] == data[pos + k - distance])
+                    .count();
+                (length, distance)
+            })
+            .max();
+
+        match best {
+            Some((length, distance)) if length >= 3 => {
+                let code = LENGTH_BASE.partition_point(|&base| base as usize <= length) - 1;
+                put_symbol(&mut bits, 257 + code as u16);
+                bits.put(
+                    (length - LENGTH_BASE[code] as usize) as u32,
+                    LENGTH_EXTRA[code],
+                );
+
+                let code = DISTANCE_BASE.partition_point(|&base| base as usize <= distance) - 1;
+                bits.put_code(code as u32, 5);
+                bits.put(
+                    (distance - DISTANCE_BASE[code] as usize) as u32,
+                    DISTANCE_EXTRA[code],
+                );
+                pos += length;
+            }
+            _ => {
+                put_symbol(&mut bits, data[pos] as u16);
+                pos += 1;
+            }
+        }
+    }
+    put_symbol(&mut bits, 256);
+
+    let mut out = vec![0x78, 0x01];
+    out.extend(bits.finish());
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/* -------------------------------------------------------------------------- */
+
+fn svg(picture: &Picture) -> String {
+    let [r, g, b] = BACKGROUND;
+    let background = format!("#{r:02x}{g:02x}{b:02x}");
+    let mut out = String::new();
+
+    match picture {
+        Picture::Raster(raster) => {
+            let side = raster.width().max(raster.height()).max(1);
+            let scale = (TARGET_SIZE / side).max(1);
+            let _ = writeln!(
+                out,
+                r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {w} {h}" width="{}" height="{}" shape-rendering="crispEdges">"#,
+                raster.width() * scale,
+                raster.height() * scale,
+                w = raster.width(),
+                h = raster.height(),
+            );
+            let _ = writeln!(
+                out,
+                r#"<rect width="100%" height="100%" fill="{background}"/>"#
+            );
+            // one rectangle per run of equally coloured cells.
+            for (y, row) in raster.rows().enumerate() {
+                let mut x = 0;
+                while x < row.len() {
+                    let colour = row[x];
+                    let run = row[x..].iter().take_while(|&&cell| cell == colour).count();
+                    if let Some(colour) = colour {
+                        let _ = writeln!(
+                            out,
+                            r#"<rect x="{x}" y="{y}" width="{run}" height="1" fill="{}"/>"#,
+                            colour.hex()
+                        );
+                    }
+                    x += run;
+                }
+            }
+        }
+        Picture::Scene(scene) => {
+            let (width, height) = scene.image_size();
+            let (extent_x, extent_y) = scene.size();
+            // dots and lines are sized in pixels of the rendered image.
+            let pixel = extent_x.max(extent_y) / TARGET_SIZE as f64;
+            let _ = writeln!(
+                out,
+                r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {extent_x} {extent_y}" width="{width}" height="{height}">"#,
+                scene.min.0, scene.min.1,
+            );
+            let _ = writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{extent_x}" height="{extent_y}" fill="{background}"/>"#,
+                scene.min.0, scene.min.1,
+            );
+            for shape in &scene.shapes {
+                let _ = match shape {
+                    Shape::Polygon { points, fill } => {
+                        let points: Vec<String> =
+                            points.iter().map(|(x, y)| format!("{x},{y}")).collect();
+                        writeln!(
+                            out,
+                            r#"<polygon points="{}" fill="{}"/>"#,
+                            points.join(" "),
+                            fill.hex()
+                        )
+                    }
+                    Shape::Line { from, to, colour } => writeln!(
+                        out,
+                        r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-linecap="round"/>"#,
+                        from.0,
+                        from.1,
+                        to.0,
+                        to.1,
+                        colour.hex(),
+                        LINE_WIDTH * pixel
+                    ),
+                    Shape::Dot { centre, colour } => writeln!(
+                        out,
+                        r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                        centre.0,
+                        centre.1,
+                        DOT_RADIUS * pixel,
+                        colour.hex()
+                    ),
+                };
+            }
+        }
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generate::Rng;
+
+    /// Decodes GIF LZW data following the specification.
+    fn unlzw(data: &[u8], min_code_size: u8) -> Vec<u8> {
+        let clear = 1_usize << min_code_size;
+        let end = clear + 1;
+        let reset = || -> Vec<Vec<u8>> {
+            let mut table: Vec<Vec<u8>> = (0..clear).map(|i| vec![i as u8]).collect();
+            table.extend([vec![], vec![]]);
+            table
+        };
+
+        let mut table = reset();
+        let mut code_size = min_code_size as u32 + 1;
+        let (mut bit_pos, mut out, mut previous) = (0, vec![], None::<usize>);
+        loop {
+            let code = (0..code_size).fold(0, |code, bit| {
+                let pos = bit_pos + bit as usize;
+                code | ((data[pos / 8] as usize >> (pos % 8)) & 1) << bit
+            });
+            bit_pos += code_size as usize;
+
+            if code == clear {
+                table = reset();
+                code_size = min_code_size as u32 + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return out;
+            }
+
+            let entry = match previous {
+                None => table[code].clone(),
+                Some(previous) => {
+                    let entry = if code < table.len() {
+                        table[code].clone()
+                    } else {
+                        let mut entry = table[previous].clone();
+                        entry.push(entry[0]);
+                        entry
+                    };
+                    let mut added = table[previous].clone();
+                    added.push(entry[0]);
+                    table.push(added);
+                    entry
+                }
+            };
+            out.extend(&entry);
+            previous = Some(code);
+            if table.len() == 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+        }
+    }
+
+    fn sample_pixels(len: usize) -> Vec<u8> {
+        let mut rng = Rng::new(44);
+        (0..len)
+            .map(|i| {
+                // long runs mixed with noise, like upscaled cells.
+                if i % 97 < 60 {
+                    (i / 300 % 9) as u8
+                } else {
+                    rng.below(16) as u8
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lzw_round_trips() {
+        for len in [0, 1, 2, 100, 20_000] {
+            let pixels = sample_pixels(len);
+            assert_eq!(unlzw(&lzw(&pixels, 4), 4), pixels, "length {len}");
+        }
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn writes_well_formed_files() {
+        let mut raster = Raster::new(3, 2, None);
+        raster[(1, 0)] = Some(Colour::Red);
+        raster[(2, 1)] = Some(Colour::Green);
+        let mut scene = Scene::around([(0.0, 0.0), (10.0, 5.0)]);
+        scene.push(Shape::Polygon {
+            points: vec![(0.0, 0.0), (10.0, 0.0), (10.0, 5.0)],
+            fill: Colour::Blue,
+        });
+        scene.push(Shape::Dot {
+            centre: (5.0, 2.0),
+            colour: Colour::Yellow,
+        });
+        let pictures = [Picture::Raster(raster), Picture::Scene(scene)];
+
+        let gif = encode(Format::Gif, &pictures, 10);
+        assert!(gif.starts_with(b"GIF89a"));
+        assert_eq!(gif.last(), Some(&0x3b));
+
+        let png = encode(Format::Apng, &pictures, 10);
+        let mut pos = 8;
+        let mut kinds = vec![];
+        while pos < png.len() {
+            let len = u32::from_be_bytes(png[pos..pos + 4].try_into().unwrap()) as usize;
+            let chunk = &png[pos + 4..pos + 8 + len];
+            let crc = u32::from_be_bytes(png[pos + 8 + len..pos + 12 + len].try_into().unwrap());
+            assert_eq!(crc32(chunk), crc);
+            kinds.push(String::from_utf8(chunk[..4].to_vec()).unwrap());
+            pos += 12 + len;
+        }
+        let expected = [
+            "IHDR", "PLTE", "acTL", "fcTL", "IDAT", "fcTL", "fdAT", "IEND",
+        ];
+        assert_eq!(kinds, expected);
+
+        let svg = String::from_utf8(encode(Format::Svg, &pictures, 10)).unwrap();
+        assert!(svg.contains("<polygon points=\"0,0 10,0 10,5\" fill=\"#4f8fff\"/>"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn fills_polygons_by_cell_centre() {
+        let mut scene = Scene::new((0.0, 0.0), (4.0, 4.0));
+        scene.push(Shape::Polygon {
+            points: vec![(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)],
+            fill: Colour::Red,
+        });
+        let raster = scene.rasterize(4, 4, 0.5, 1.0);
+        let filled: Vec<(usize, usize)> = raster
+            .positions()
+            .filter(|&pos| raster[pos].is_some())
+            .collect();
+        assert_eq!(filled, [(1, 1), (2, 1), (1, 2), (2, 2)]);
+    }
+}
@@ -0,0 +1,506 @@
+//! Generic graph searches over a user-supplied successor function.
+//!
+//! Nodes are any `Clone + Eq + Hash` value, so the same helpers work for grid coordinates,
+//! packed states or labels. Every search accepts multiple start nodes; they are all treated as
+//! being at distance zero.
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+const NO_PARENT: usize = usize::MAX;
+
+/// Interns nodes into dense indices and remembers how each node was reached.
+struct Visited<N> {
+    nodes: Vec<N>,
+    parents: Vec<usize>,
+    index: HashMap<N, usize>,
+}
+
+impl<N: Clone + Eq + Hash> Visited<N> {
+    fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            parents: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    /// Returns the index of `node` and whether it was newly inserted.
+    fn insert(&mut self, node: N, parent: usize) -> (usize, bool) {
+        if let Some(&idx) = self.index.get(&node) {
+            return (idx, false);
+        }
+        let idx = self.nodes.len();
+        self.index.insert(node.clone(), idx);
+        self.nodes.push(node);
+        self.parents.push(parent);
+        (idx, true)
+    }
+
+    fn path_to(&self, mut idx: usize) -> Vec<N> {
+        let mut path = vec![self.nodes[idx].clone()];
+        while self.parents[idx] != NO_PARENT {
+            idx = self.parents[idx];
+            path.push(self.nodes[idx].clone());
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// Breadth-first search from `starts` to the first node accepted by `success`.
+///
+/// Returns the shortest path including both endpoints.
+pub fn bfs<N, FN, IN, FS>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: FN,
+    mut success: FS,
+) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FS: FnMut(&N) -> bool,
+{
+    let mut visited = Visited::new();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        let (idx, is_new) = visited.insert(start, NO_PARENT);
+        if is_new {
+            queue.push_back(idx);
+        }
+    }
+
+    while let Some(idx) = queue.pop_front() {
+        if success(&visited.nodes[idx]) {
+            return Some(visited.path_to(idx));
+        }
+        for next in successors(&visited.nodes[idx]) {
+            let (next_idx, is_new) = visited.insert(next, idx);
+            if is_new {
+                queue.push_back(next_idx);
+            }
+        }
+    }
+
+    None
+}
+
+/// Breadth-first flood from `starts`, returning the distance to every reachable node.
+pub fn bfs_distances<N, FN, IN>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: FN,
+) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        if !distances.contains_key(&start) {
+            distances.insert(start.clone(), 0);
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((node, distance)) = queue.pop_front() {
+        for next in successors(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+
+    distances
+}
+
+/// Counts the shortest paths from `starts` to the nearest nodes accepted by `success`.
+///
+/// Returns the shortest distance and the number of distinct shortest paths, summed over every
+/// goal node at that distance.
+pub fn count_shortest_paths<N, FN, IN, FS>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: FN,
+    mut success: FS,
+) -> Option<(usize, u64)>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FS: FnMut(&N) -> bool,
+{
+    let mut layer: HashMap<N, u64> = HashMap::new();
+    for start in starts {
+        *layer.entry(start).or_default() += 1;
+    }
+    let mut seen: HashSet<N> = layer.keys().cloned().collect();
+    let mut distance = 0;
+
+    while !layer.is_empty() {
+        let found: u64 = layer
+            .iter()
+            .filter(|(node, _)| success(node))
+            .map(|(_, count)| count)
+            .sum();
+        if found > 0 {
+            return Some((distance, found));
+        }
+
+        let mut next_layer: HashMap<N, u64> = HashMap::new();
+        for (node, count) in &layer {
+            for next in successors(node) {
+                if seen.contains(&next) && !next_layer.contains_key(&next) {
+                    continue;
+                }
+                seen.insert(next.clone());
+                *next_layer.entry(next).or_default() += count;
+            }
+        }
+
+        layer = next_layer;
+        distance += 1;
+    }
+
+    None
+}
+
+/// Dijkstra's algorithm from `starts` to the first node accepted by `success`.
+///
+/// Returns the cheapest path including both endpoints, together with its total cost.
+pub fn dijkstra<N, C, FN, IN, FS>(
+    starts: impl IntoIterator<Item = N>,
+    successors: FN,
+    success: FS,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FS: FnMut(&N) -> bool,
+{
+    astar(starts, successors, |_| C::default(), success)
+}
+
+/// A* search from `starts` to the first node accepted by `success`.
+///
+/// `heuristic` must never overestimate the remaining cost, otherwise the returned path is not
+/// guaranteed to be the cheapest one.
+pub fn astar<N, C, FN, IN, FH, FS>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut success: FS,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FS: FnMut(&N) -> bool,
+{
+    let mut visited = Visited::new();
+    let mut costs: Vec<C> = Vec::new();
+    let mut heap = BinaryHeap::new();
+
+    for start in starts {
+        let (idx, is_new) = visited.insert(start, NO_PARENT);
+        if is_new {
+            costs.push(C::default());
+            heap.push(Reverse((heuristic(&visited.nodes[idx]), C::default(), idx)));
+        }
+    }
+
+    while let Some(Reverse((_, cost, idx))) = heap.pop() {
+        if cost > costs[idx] {
+            continue;
+        }
+        if success(&visited.nodes[idx]) {
+            return Some((visited.path_to(idx), cost));
+        }
+        for (next, step) in successors(&visited.nodes[idx]) {
+            let next_cost = cost + step;
+            let (next_idx, is_new) = visited.insert(next, idx);
+            if is_new {
+                costs.push(next_cost);
+            } else if next_cost < costs[next_idx] {
+                costs[next_idx] = next_cost;
+                visited.parents[next_idx] = idx;
+            } else {
+                continue;
+            }
+            let estimate = next_cost + heuristic(&visited.nodes[next_idx]);
+            heap.push(Reverse((estimate, next_cost, next_idx)));
+        }
+    }
+
+    None
+}
+
+/// Counts the cheapest paths from `starts` to the nearest nodes accepted by `success`.
+///
+/// Step costs must be strictly positive. Returns the cheapest cost and the number of distinct
+/// paths achieving it, summed over every goal node with that cost.
+pub fn dijkstra_count_paths<N, C, FN, IN, FS>(
+    starts: impl IntoIterator<Item = N>,
+    mut successors: FN,
+    mut success: FS,
+) -> Option<(C, u64)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FS: FnMut(&N) -> bool,
+{
+    let mut visited = Visited::new();
+    let mut costs: Vec<C> = Vec::new();
+    let mut counts: Vec<u64> = Vec::new();
+    let mut heap = BinaryHeap::new();
+
+    for start in starts {
+        let (idx, is_new) = visited.insert(start, NO_PARENT);
+        if is_new {
+            costs.push(C::default());
+            counts.push(1);
+            heap.push(Reverse((C::default(), idx)));
+        }
+    }
+
+    let mut best: Option<(C, u64)> = None;
+
+    while let Some(Reverse((cost, idx))) = heap.pop() {
+        if cost > costs[idx] {
+            continue;
+        }
+        if let Some((best_cost, _)) = best
+            && cost > best_cost
+        {
+            break;
+        }
+        if success(&visited.nodes[idx]) {
+            let (_, found) = best.get_or_insert((cost, 0));
+            *found += counts[idx];
+            continue;
+        }
+        for (next, step) in successors(&visited.nodes[idx]) {
+            let next_cost = cost + step;
+            let (next_idx, is_new) = visited.insert(next, idx);
+            if is_new {
+                costs.push(next_cost);
+                counts.push(counts[idx]);
+            } else if next_cost < costs[next_idx] {
+                costs[next_idx] = next_cost;
+                counts[next_idx] = counts[idx];
+            } else {
+                if next_cost == costs[next_idx] {
+                    counts[next_idx] += counts[idx];
+                }
+                continue;
+            }
+            heap.push(Reverse((next_cost, next_idx)));
+        }
+    }
+
+    best
+}
+
+/// Bidirectional breadth-first search between `start` and `goal`.
+///
+/// `successors` expands the forward frontier and `predecessors` the backward one. For undirected
+/// graphs both are the same neighbour function.
+pub fn bidirectional_bfs<N, FN, IN, FP, IP>(
+    start: N,
+    goal: N,
+    mut successors: FN,
+    mut predecessors: FP,
+) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FP: FnMut(&N) -> IP,
+    IP: IntoIterator<Item = N>,
+{
+    if start == goal {
+        return Some(vec![start]);
+    }
+
+    let mut forward = Visited::new();
+    let mut backward = Visited::new();
+    let mut forward_layer = vec![forward.insert(start, NO_PARENT).0];
+    let mut backward_layer = vec![backward.insert(goal, NO_PARENT).0];
+
+    while !forward_layer.is_empty() && !backward_layer.is_empty() {
+        // always grow the smaller frontier by one full layer.
+        let grow_forward = forward_layer.len() <= backward_layer.len();
+        let (this, other, layer) = if grow_forward {
+            (&mut forward, &backward, &mut forward_layer)
+        } else {
+            (&mut backward, &forward, &mut backward_layer)
+        };
+
+        let mut next_layer = Vec::new();
+        let mut meeting = None;
+        for &idx in layer.iter() {
+            let neighbours: Vec<N> = if grow_forward {
+                successors(&this.nodes[idx]).into_iter().collect()
+            } else {
+                predecessors(&this.nodes[idx]).into_iter().collect()
+            };
+            for next in neighbours {
+                let (next_idx, is_new) = this.insert(next, idx);
+                if !is_new {
+                    continue;
+                }
+                if meeting.is_none() && other.index.contains_key(&this.nodes[next_idx]) {
+                    meeting = Some(next_idx);
+                }
+                next_layer.push(next_idx);
+            }
+        }
+
+        if let Some(idx) = meeting {
+            let node = &this.nodes[idx];
+            let mut head = this.path_to(idx);
+            let mut tail = other.path_to(other.index[node]);
+            if !grow_forward {
+                std::mem::swap(&mut head, &mut tail);
+            }
+            tail.pop();
+            head.extend(tail.into_iter().rev());
+            return Some(head);
+        }
+
+        *layer = next_layer;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    const MAZE: &str = "\
+S..#....
+.#.#.##.
+.#...#..
+.####.#.
+......#E";
+
+    type Pos = (usize, usize);
+
+    fn maze() -> (Grid<u8>, Pos, Pos) {
+        let grid = Grid::parse(MAZE, |b| b).unwrap();
+        let find = |ch: u8| grid.positions().find(|&p| grid[p] == ch).unwrap();
+        let (start, end) = (find(b'S'), find(b'E'));
+        (grid, start, end)
+    }
+
+    fn neighbours(grid: &Grid<u8>, pos: Pos) -> Vec<Pos> {
+        grid.orthogonal_neighbours(pos)
+            .filter(|&p| grid[p] != b'#')
+            .collect()
+    }
+
+    #[test]
+    fn bfs_finds_shortest_path() {
+        let (grid, start, end) = maze();
+        let path = bfs([start], |&p| neighbours(&grid, p), |&p| p == end).unwrap();
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&end));
+        assert_eq!(path.len() - 1, 15);
+        assert!(
+            path.windows(2)
+                .all(|w| neighbours(&grid, w[0]).contains(&w[1]))
+        );
+
+        let distances = bfs_distances([start], |&p| neighbours(&grid, p));
+        assert_eq!(distances[&end], 15);
+    }
+
+    #[test]
+    fn bfs_supports_multiple_sources() {
+        let (grid, start, end) = maze();
+        let path = bfs([start, (6, 2)], |&p| neighbours(&grid, p), |&p| p == end).unwrap();
+        assert_eq!(path.first(), Some(&(6, 2)));
+        assert_eq!(path.len() - 1, 3);
+        assert_eq!(
+            bfs([start], |&p| neighbours(&grid, p), |&p| p == (0, 9)),
+            None
+        );
+    }
+
+    #[test]
+    fn dijkstra_and_astar_agree() {
+        let (grid, start, end) = maze();
+        // moving right is cheap, every other direction costs more.
+        let weighted = |&(x, y): &(usize, usize)| {
+            neighbours(&grid, (x, y))
+                .into_iter()
+                .map(move |(nx, ny)| ((nx, ny), if nx > x { 1_u32 } else { 3 }))
+        };
+        let manhattan = |&(x, y): &(usize, usize)| (end.0.abs_diff(x) + end.1.abs_diff(y)) as u32;
+
+        let (path, cost) = dijkstra([start], weighted, |&p| p == end).unwrap();
+        let (astar_path, astar_cost) = astar([start], weighted, manhattan, |&p| p == end).unwrap();
+        assert_eq!(cost, astar_cost);
+        assert_eq!(path.len(), astar_path.len());
+        assert_eq!(cost, 31);
+    }
+
+    #[test]
+    fn counts_all_shortest_paths() {
+        // on an open 3x3 grid there are C(4, 2) = 6 shortest corner-to-corner paths.
+        let open = |&(x, y): &(i32, i32)| {
+            [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+                .into_iter()
+                .filter(|&(x, y)| (0..3).contains(&x) && (0..3).contains(&y))
+        };
+        assert_eq!(
+            count_shortest_paths([(0, 0)], open, |&p| p == (2, 2)),
+            Some((4, 6))
+        );
+        assert_eq!(
+            dijkstra_count_paths([(0, 0)], |p| open(p).map(|n| (n, 1_u32)), |&p| p == (2, 2)),
+            Some((4, 6))
+        );
+    }
+
+    #[test]
+    fn bidirectional_matches_bfs() {
+        let (grid, start, end) = maze();
+        let path = bidirectional_bfs(
+            start,
+            end,
+            |&p| neighbours(&grid, p),
+            |&p| neighbours(&grid, p),
+        )
+        .unwrap();
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&end));
+        assert_eq!(path.len() - 1, 15);
+        assert!(
+            path.windows(2)
+                .all(|w| neighbours(&grid, w[0]).contains(&w[1]))
+        );
+    }
+
+    #[test]
+    fn bfs_moves_diagonally_with_all_neighbours() {
+        let (grid, start, end) = maze();
+        let open = |&p: &Pos| grid.neighbours(p).filter(|&n| grid[n] != b'#');
+        let path = bfs([start], open, |&p| p == end).unwrap();
+        assert_eq!(path.len() - 1, 8);
+        assert!(path.windows(2).all(|w| open(&w[0]).any(|n| n == w[1])));
+    }
+}
@@ -0,0 +1,299 @@
+//! A k-d tree over integer points for nearest-neighbour and closest-pair queries.
+//!
+//! Distances are squared euclidean distances in `i64`, so coordinates need to stay well below
+//! `2^31` per axis.
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+const LEAF_SIZE: usize = 8;
+
+#[derive(Debug, Clone)]
+struct Node<const K: usize> {
+    /// Range of `KdTree::order` covered by this node.
+    start: usize,
+    end: usize,
+    min: [i64; K],
+    max: [i64; K],
+    children: Option<(usize, usize)>,
+}
+
+/// A static k-d tree. Points are referred to by their index in the slice the tree was built from.
+#[derive(Debug, Clone)]
+pub struct KdTree<const K: usize> {
+    points: Vec<[i64; K]>,
+    order: Vec<usize>,
+    nodes: Vec<Node<K>>,
+}
+
+/// Squared euclidean distance between two points.
+#[inline]
+pub fn distance_squared<const K: usize>(a: &[i64; K], b: &[i64; K]) -> i64 {
+    (0..K).map(|d| (a[d] - b[d]) * (a[d] - b[d])).sum()
+}
+
+impl<const K: usize> KdTree<K> {
+    pub fn new(points: impl IntoIterator<Item = [i64; K]>) -> Self {
+        let points: Vec<[i64; K]> = points.into_iter().collect();
+        let mut tree = Self {
+            order: (0..points.len()).collect(),
+            points,
+            nodes: Vec::new(),
+        };
+        if !tree.points.is_empty() {
+            tree.build(0, tree.points.len());
+        }
+        tree
+    }
+
+    /// Builds the subtree over `order[start..end]` and returns its node index.
+    fn build(&mut self, start: usize, end: usize) -> usize {
+        let mut min = [i64::MAX; K];
+        let mut max = [i64::MIN; K];
+        for &idx in &self.order[start..end] {
+            for d in 0..K {
+                min[d] = min[d].min(self.points[idx][d]);
+                max[d] = max[d].max(self.points[idx][d]);
+            }
+        }
+
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            start,
+            end,
+            min,
+            max,
+            children: None,
+        });
+
+        if end - start > LEAF_SIZE {
+            // split the widest dimension at its median.
+            let dim = (0..K).max_by_key(|&d| max[d] - min[d]).unwrap_or(0);
+            let mid = start + (end - start) / 2;
+            let points = &self.points;
+            self.order[start..end].select_nth_unstable_by_key(mid - start, |&idx| points[idx][dim]);
+            let left = self.build(start, mid);
+            let right = self.build(mid, end);
+            self.nodes[node].children = Some((left, right));
+        }
+
+        node
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, idx: usize) -> &[i64; K] {
+        &self.points[idx]
+    }
+
+    /// Smallest squared distance from `query` to the bounding box of `node`.
+    fn box_distance(&self, node: usize, query: &[i64; K]) -> i64 {
+        let node = &self.nodes[node];
+        (0..K)
+            .map(|d| {
+                let delta = (node.min[d] - query[d]).max(query[d] - node.max[d]).max(0);
+                delta * delta
+            })
+            .sum()
+    }
+
+    /// Iterates all points by increasing distance to `query`, yielding `(distance², index)`.
+    /// Ties are broken by index.
+    pub fn neighbours(&self, query: [i64; K]) -> Neighbours<'_, K> {
+        let mut heap = BinaryHeap::new();
+        if !self.is_empty() {
+            heap.push(Reverse((self.box_distance(0, &query), Entry::Node, 0)));
+        }
+        Neighbours {
+            tree: self,
+            query,
+            heap,
+        }
+    }
+
+    /// The `k` nearest points to `query` as `(distance², index)`, closest first.
+    pub fn nearest(&self, query: [i64; K], k: usize) -> Vec<(i64, usize)> {
+        self.neighbours(query).take(k).collect()
+    }
+
+    /// Indices of all points within `radius_squared` of `query`, in ascending order.
+    pub fn within_radius(&self, query: [i64; K], radius_squared: i64) -> Vec<usize> {
+        let mut result = vec![];
+        let mut stack = if self.is_empty() { vec![] } else { vec![0] };
+        while let Some(node) = stack.pop() {
+            if self.box_distance(node, &query) > radius_squared {
+                continue;
+            }
+            match self.nodes[node].children {
+                Some((left, right)) => stack.extend([left, right]),
+                None => {
+                    let Node { start, end, .. } = self.nodes[node];
+                    result.extend(self.order[start..end].iter().filter(|&&idx| {
+                        distance_squared(&self.points[idx], &query) <= radius_squared
+                    }));
+                }
+            }
+        }
+        result.sort_unstable();
+        result
+    }
+
+    /// Lazily iterates every pair of points by increasing distance, yielding `(distance², i, j)`
+    /// with `i < j`. Ties are broken by `(i, j)`.
+    ///
+    /// Only as many pairs as are consumed get generated, which makes this suitable for streaming
+    /// Kruskal-style edge lists without materializing all `n²` pairs.
+    pub fn closest_pairs(&self) -> ClosestPairs<'_, K> {
+        let mut cursors: Vec<Neighbours<'_, K>> = (0..self.len())
+            .map(|idx| self.neighbours(self.points[idx]))
+            .collect();
+        let mut heap = BinaryHeap::with_capacity(self.len());
+        for (i, cursor) in cursors.iter_mut().enumerate() {
+            if let Some((distance, j)) = next_partner(cursor, i) {
+                heap.push(Reverse((distance, i, j)));
+            }
+        }
+        ClosestPairs { cursors, heap }
+    }
+}
+
+/// Advances a neighbour cursor of point `i` to the next point with a larger index.
+fn next_partner<const K: usize>(cursor: &mut Neighbours<'_, K>, i: usize) -> Option<(i64, usize)> {
+    cursor.find(|&(_, j)| j > i)
+}
+
+/// Heap entries of [`Neighbours`]. At equal distances nodes sort before points, so that every
+/// point at that distance is known before the first one is yielded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Entry {
+    Node,
+    Point,
+}
+
+/// Iterator returned by [`KdTree::neighbours`].
+pub struct Neighbours<'a, const K: usize> {
+    tree: &'a KdTree<K>,
+    query: [i64; K],
+    heap: BinaryHeap<Reverse<(i64, Entry, usize)>>,
+}
+
+impl<const K: usize> Iterator for Neighbours<'_, K> {
+    type Item = (i64, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse((distance, entry, id))) = self.heap.pop() {
+            if entry == Entry::Point {
+                return Some((distance, id));
+            }
+            match self.tree.nodes[id].children {
+                Some((left, right)) => {
+                    for child in [left, right] {
+                        let bound = self.tree.box_distance(child, &self.query);
+                        self.heap.push(Reverse((bound, Entry::Node, child)));
+                    }
+                }
+                None => {
+                    let Node { start, end, .. } = self.tree.nodes[id];
+                    for &idx in &self.tree.order[start..end] {
+                        let distance = distance_squared(&self.tree.points[idx], &self.query);
+                        self.heap.push(Reverse((distance, Entry::Point, idx)));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Iterator returned by [`KdTree::closest_pairs`].
+pub struct ClosestPairs<'a, const K: usize> {
+    cursors: Vec<Neighbours<'a, K>>,
+    heap: BinaryHeap<Reverse<(i64, usize, usize)>>,
+}
+
+impl<const K: usize> Iterator for ClosestPairs<'_, K> {
+    type Item = (i64, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((distance, i, j)) = self.heap.pop()?;
+        if let Some((next_distance, next_j)) = next_partner(&mut self.cursors[i], i) {
+            self.heap.push(Reverse((next_distance, i, next_j)));
+        }
+        Some((distance, i, j))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scattered_points(n: usize) -> Vec<[i64; 3]> {
+        // deterministic pseudo-random coordinates with plenty of duplicate distances.
+        (0..n as i64)
+            .map(|i| [(i * 7919) % 101, (i * 104_729) % 89, (i * 1_299_709) % 97])
+            .collect()
+    }
+
+    fn all_pairs(points: &[[i64; 3]]) -> Vec<(i64, usize, usize)> {
+        let mut pairs = vec![];
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                pairs.push((distance_squared(&points[i], &points[j]), i, j));
+            }
+        }
+        pairs.sort_unstable();
+        pairs
+    }
+
+    #[test]
+    fn nearest_neighbours_match_brute_force() {
+        let points = scattered_points(300);
+        let tree = KdTree::new(points.clone());
+        let query = [50, 40, 30];
+
+        let mut expected: Vec<(i64, usize)> = points
+            .iter()
+            .enumerate()
+            .map(|(idx, p)| (distance_squared(p, &query), idx))
+            .collect();
+        expected.sort_unstable();
+
+        assert_eq!(tree.nearest(query, 10), expected[..10]);
+        assert_eq!(tree.neighbours(query).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn radius_query_matches_brute_force() {
+        let points = scattered_points(300);
+        let tree = KdTree::new(points.clone());
+        let query = [10, 80, 60];
+        let expected: Vec<usize> = (0..points.len())
+            .filter(|&idx| distance_squared(&points[idx], &query) <= 400)
+            .collect();
+        assert!(!expected.is_empty());
+        assert_eq!(tree.within_radius(query, 400), expected);
+    }
+
+    #[test]
+    fn closest_pairs_are_ordered() {
+        let points = scattered_points(120);
+        let tree = KdTree::new(points.clone());
+        assert_eq!(tree.closest_pairs().collect::<Vec<_>>(), all_pairs(&points));
+    }
+
+    #[test]
+    fn handles_small_inputs() {
+        let empty = KdTree::<2>::new([]);
+        assert_eq!(empty.nearest([0, 0], 3), []);
+        assert_eq!(empty.closest_pairs().next(), None);
+
+        let single = KdTree::new([[1, 1]]);
+        assert_eq!(single.nearest([0, 0], 3), [(2, 0)]);
+        assert_eq!(single.closest_pairs().next(), None);
+    }
+}
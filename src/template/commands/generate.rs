@@ -0,0 +1,30 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+use crate::generate;
+use crate::template::Day;
+
+pub fn handle(day: Day, size: f64, seed: u64) {
+    if size.is_nan() || size <= 0.0 {
+        eprintln!("The size must be positive, got {size}.");
+        process::exit(1);
+    }
+
+    let Some(input) = generate::input(day, size, seed) else {
+        eprintln!("There is no input generator for day {day}.");
+        process::exit(1);
+    };
+
+    let path = PathBuf::from("data/generated").join(format!("{day}.txt"));
+    if let Err(err) = fs::create_dir_all("data/generated").and_then(|()| fs::write(&path, &input)) {
+        eprintln!("Failed to write generated input: {err}");
+        process::exit(1);
+    }
+
+    println!(
+        "Generated {} lines of input for day {day} (size {size}, seed {seed}) in {}",
+        input.lines().count(),
+        path.display()
+    );
+}
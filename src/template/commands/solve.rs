@@ -1,27 +1,126 @@
 use std::process::{Command, Stdio};
 
 use crate::template::Day;
+use crate::viz;
 
-pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
+/// Flags of `cargo solve`, forwarded to the day's binary. The binary reads them back once with
+/// [`SolveOptions::from_args`] and hands them to the runner.
+#[derive(Debug, Clone, Default)]
+pub struct SolveOptions {
+    /// Build with optimizations. Chooses how the binary is built, so it's never passed to it.
+    pub release: bool,
+    /// Build with the heap profiler. Never passed to the binary either.
+    pub dhat: bool,
+    pub submit: Option<u8>,
+    pub visualize: Option<viz::Options>,
+    pub explain: bool,
+    pub json: bool,
+    pub profile_lines: bool,
+    /// Lines listed by `--profile-lines`, if not the default.
+    pub slowest: Option<usize>,
+    pub generated: bool,
+    /// Check witnesses in release builds, as done by `cargo verify`.
+    pub verify: bool,
+    /// Benchmark every part, as done by `cargo time`. Benchmarks run every part many times, so
+    /// they turn off explaining, witness checks and visualizations.
+    pub time: bool,
+}
+
+impl SolveOptions {
+    /// Reads the flags a day's binary was started with.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let value = |name: &str| {
+            let idx = args.iter().position(|arg| arg == name)?;
+            Some(args.get(idx + 1).map_or("", String::as_str))
+        };
+        let flag = |name: &str| args.iter().any(|arg| arg == name);
+
+        let submit = match value("--submit") {
+            None => None,
+            Some(part) => Some(part.parse().map_err(|_| {
+                "Unexpected command-line input. Format: cargo solve 1 --submit 1".to_string()
+            })?),
+        };
+        let slowest = match value("--slowest") {
+            None => None,
+            Some(count) => Some(
+                count
+                    .parse()
+                    .map_err(|_| format!("invalid --slowest `{count}`"))?,
+            ),
+        };
+
+        Ok(Self {
+            release: false,
+            dhat: false,
+            submit,
+            visualize: viz::Options::from_args(args),
+            explain: flag("--explain"),
+            json: flag("--json"),
+            profile_lines: flag("--profile-lines"),
+            slowest,
+            generated: flag("--generated"),
+            verify: flag("--verify"),
+            time: flag("--time"),
+        })
+    }
+
+    /// The arguments that make a day's binary run with these options.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(submit_part) = self.submit {
+            args.extend(["--submit".to_string(), submit_part.to_string()]);
+        }
+        if let Some(visualize) = &self.visualize {
+            args.extend(visualize.to_args());
+        }
+        let flags = [
+            (self.explain, "--explain"),
+            (self.json, "--json"),
+            (self.generated, "--generated"),
+            (self.verify, "--verify"),
+            (self.time, "--time"),
+            (self.profile_lines, "--profile-lines"),
+        ];
+        args.extend(
+            flags
+                .iter()
+                .filter(|(on, _)| *on)
+                .map(|(_, flag)| flag.to_string()),
+        );
+        if let (true, Some(slowest)) = (self.profile_lines, self.slowest) {
+            args.extend(["--slowest".to_string(), slowest.to_string()]);
+        }
+        args
+    }
+
+    /// Whether witnesses are checked: always in debug builds, and with `--verify` in release ones.
+    pub fn verifies(&self) -> bool {
+        (cfg!(debug_assertions) || self.verify) && !self.time
+    }
+
+    /// Whether parts record rows for `--explain` and `--json`.
+    pub fn explains(&self) -> bool {
+        (self.explain || self.json) && !self.time
+    }
+}
+
+pub fn handle(day: Day, options: SolveOptions) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
 
-    if dhat {
+    if options.dhat {
         cmd_args.extend([
             "--profile".to_string(),
             "dhat".to_string(),
             "--features".to_string(),
             "dhat-heap".to_string(),
         ]);
-    } else if release {
+    } else if options.release {
         cmd_args.push("--release".to_string());
     }
 
     cmd_args.push("--".to_string());
-
-    if let Some(submit_part) = submit_part {
-        cmd_args.push("--submit".to_string());
-        cmd_args.push(submit_part.to_string());
-    }
+    cmd_args.extend(options.to_args());
 
     let mut cmd = Command::new("cargo")
         .args(&cmd_args)
@@ -32,3 +131,40 @@ pub fn handle(day: Day, release: bool, dhat: bool, submit_part: Option<u8>) {
 
     cmd.wait().unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_args() {
+        let options = SolveOptions {
+            submit: Some(2),
+            explain: true,
+            profile_lines: true,
+            slowest: Some(3),
+            generated: true,
+            ..SolveOptions::default()
+        };
+        let parsed = SolveOptions::from_args(&options.to_args()).unwrap();
+        assert_eq!(parsed.to_args(), options.to_args());
+        assert_eq!(
+            options.to_args(),
+            [
+                "--submit",
+                "2",
+                "--explain",
+                "--generated",
+                "--profile-lines",
+                "--slowest",
+                "3"
+            ]
+        );
+
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert!(SolveOptions::from_args(&args(&["--submit", "x"])).is_err());
+        assert!(SolveOptions::from_args(&args(&["--submit"])).is_err());
+        let timed = SolveOptions::from_args(&args(&["--explain", "--time"])).unwrap();
+        assert!(!timed.explains());
+    }
+}
@@ -1,10 +1,16 @@
 use std::collections::HashSet;
+use std::process;
 
 use crate::template::run_multi::run_multi;
 use crate::template::timings::Timings;
 use crate::template::{Day, all_days, readme_benchmarks};
 
-pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
+pub fn handle(day: Option<Day>, run_all: bool, store: bool, generated: bool) {
+    if store && generated {
+        eprintln!("Not storing timings for generated inputs, they would replace the real ones.");
+        process::exit(1);
+    }
+
     let stored_timings = Timings::read_from_file();
 
     let days_to_run = day.map_or_else(
@@ -21,7 +27,7 @@ pub fn handle(day: Option<Day>, run_all: bool, store: bool) {
         |day| HashSet::from([day]),
     );
 
-    let timings = run_multi(&days_to_run, true, true).unwrap();
+    let timings = run_multi(&days_to_run, true, true, generated).unwrap();
 
     if store {
         let merged_timings = stored_timings.merge(&timings);
@@ -0,0 +1,52 @@
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+
+use crate::template::commands::solve::SolveOptions;
+use crate::template::run_multi::get_path_for_bin;
+use crate::template::{ANSI_BOLD, ANSI_RESET, Day, all_days};
+
+/// Solves `day`, or every scaffolded day with an input, in release mode with witness checks on.
+pub fn handle(day: Option<Day>) {
+    let days: Vec<Day> = match day {
+        Some(day) => vec![day],
+        None => all_days()
+            .filter(|&day| Path::new(&get_path_for_bin(day)).exists())
+            .collect(),
+    };
+
+    let verify_options = SolveOptions {
+        verify: true,
+        ..SolveOptions::default()
+    };
+    let mut failed = vec![];
+    for (idx, &day) in days.iter().enumerate() {
+        if idx > 0 {
+            println!();
+        }
+        println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
+        println!("------");
+
+        if !Path::new(&format!("data/inputs/{day}.txt")).exists() {
+            println!("No input.");
+            continue;
+        }
+
+        let status = Command::new("cargo")
+            .args(["run", "--quiet", "--release", "--bin", &day.to_string()])
+            .arg("--")
+            .args(verify_options.to_args())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .unwrap();
+        if !status.success() {
+            failed.push(day);
+        }
+    }
+
+    if !failed.is_empty() {
+        let failed: Vec<String> = failed.iter().map(Day::to_string).collect();
+        eprintln!("\nVerification failed for day {}.", failed.join(", "));
+        process::exit(1);
+    }
+}
@@ -1,8 +1,12 @@
 use std::{env, fs};
 
+use commands::solve::SolveOptions;
+
 pub mod aoc_cli;
 pub mod commands;
+pub mod profile;
 pub mod runner;
+pub mod variants;
 
 pub use day::*;
 
@@ -24,6 +28,20 @@ pub fn read_file(folder: &str, day: Day) -> String {
     f.expect("could not open input file")
 }
 
+/// Reads the puzzle input for `day`, or the input written by `cargo gen` if the binary was
+/// started with `--generated`.
+#[must_use]
+pub fn read_input(day: Day, options: &SolveOptions) -> String {
+    read_file(
+        if options.generated {
+            "generated"
+        } else {
+            "inputs"
+        },
+        day,
+    )
+}
+
 /// Helper function that reads a text file to string, appending a part suffix. E.g. like `01-2.txt`.
 #[must_use]
 pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
@@ -39,30 +57,55 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+/// Days that solve every input line on its own can instead pass `lines: [one, two]`, functions
+/// that solve a single line for each part, which `--profile-lines` times line by line. Alternative
+/// implementations of the parts are registered separately with [`variants!`](crate::variants).
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
-        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2]);
+        $crate::solution!(@impl $day,
+            [part_one, 1, None::<fn(&str)>, part_one_variants]
+            [part_two, 2, None::<fn(&str)>, part_two_variants]);
     };
     ($day:expr, 1) => {
-        $crate::solution!(@impl $day, [part_one, 1]);
+        $crate::solution!(@impl $day, [part_one, 1, None::<fn(&str)>, part_one_variants]);
     };
     ($day:expr, 2) => {
-        $crate::solution!(@impl $day, [part_two, 2]);
+        $crate::solution!(@impl $day, [part_two, 2, None::<fn(&str)>, part_two_variants]);
+    };
+    ($day:expr, lines: [$line_one:expr, $line_two:expr]) => {
+        $crate::solution!(@impl $day,
+            [part_one, 1, Some($line_one), part_one_variants]
+            [part_two, 2, Some($line_two), part_two_variants]);
     };
 
-    (@impl $day:expr, $( [$func:expr, $part:expr] )*) => {
+    (@impl $day:expr, $( [$func:expr, $part:expr, $line_func:expr, $variants:ident] )*) => {
         /// The current day.
         const DAY: $crate::template::Day = $crate::day!($day);
 
+        // replaced by the functions `variants!` defines, if the day registers variants.
+        #[allow(unused_imports)]
+        use $crate::template::variants::none::*;
+
         #[cfg(feature = "dhat-heap")]
         #[global_allocator]
         static ALLOC: dhat::Alloc = dhat::Alloc;
 
         fn main() {
             use $crate::template::runner::*;
-            let input = $crate::template::read_file("inputs", DAY);
-            $( run_part($func, &input, DAY, $part); )*
+            let options = parse_options();
+            $crate::explain::enable(options.explains());
+            $crate::verify::enable(options.verifies());
+            let input = $crate::template::read_input(DAY, &options);
+            if options.profile_lines {
+                $( $crate::template::profile::profile_lines($line_func, &input, $part, &options); )*
+                return;
+            }
+            $(
+                run_part($func, &input, DAY, $part, &options);
+                run_variants(&$variants(), &input, $part, &options);
+            )*
+            $crate::viz::finish();
         }
     };
 }
@@ -0,0 +1,137 @@
+/// Times a solution line by line, for days whose lines are solved independently.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::explain::{self, Cell, Table};
+use crate::template::commands::solve::SolveOptions;
+use crate::template::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+/// Every line is solved this often and its fastest run kept, which filters out most noise.
+const RUNS_PER_LINE: usize = 5;
+/// Lines shown when `--slowest` isn't given.
+const DEFAULT_SLOWEST: usize = 10;
+/// Input shown per line, longer lines are cut off.
+const INPUT_WIDTH: usize = 60;
+const BAR_WIDTH: usize = 40;
+
+/// Solves every non-empty line of `input` with `solve_line` and prints the slowest lines and a
+/// histogram of how long lines took. The number of lines shown is taken from `--slowest <n>`.
+pub fn profile_lines<R>(
+    solve_line: Option<impl Fn(&str) -> R>,
+    input: &str,
+    part: u8,
+    options: &SolveOptions,
+) {
+    let Some(solve_line) = solve_line else {
+        println!("Part {part}: {ANSI_ITALIC}no per-line function to profile{ANSI_RESET}");
+        return;
+    };
+
+    let lines: Vec<(usize, &str)> = (input.lines().enumerate())
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    let mut timings: Vec<(Duration, usize, &str)> = lines
+        .iter()
+        .map(|&(idx, line)| {
+            let fastest = (0..RUNS_PER_LINE)
+                .map(|_| {
+                    let timer = Instant::now();
+                    black_box(solve_line(black_box(line)));
+                    timer.elapsed()
+                })
+                .min()
+                .unwrap_or_default();
+            (fastest, idx + 1, line)
+        })
+        .collect();
+
+    let total: Duration = timings.iter().map(|&(time, _, _)| time).sum();
+    println!(
+        "Part {part}: {ANSI_BOLD}{} lines{ANSI_RESET} in {total:.1?} {ANSI_ITALIC}(fastest of {RUNS_PER_LINE} runs each){ANSI_RESET}",
+        timings.len()
+    );
+    if timings.is_empty() {
+        return;
+    }
+
+    let histogram = histogram(&timings.iter().map(|&(time, _, _)| time).collect::<Vec<_>>());
+    timings.sort_by_key(|&(time, _, _)| std::cmp::Reverse(time));
+    let count = options.slowest.unwrap_or(DEFAULT_SLOWEST);
+    print!("{}", explain::render(&[slowest(&timings, total, count)]));
+    println!("{ANSI_BOLD}latency{ANSI_RESET}");
+    print!("{histogram}");
+}
+
+fn slowest(timings: &[(Duration, usize, &str)], total: Duration, count: usize) -> Table {
+    let rows = timings.iter().take(count).map(|&(time, line, input)| {
+        let share = time.as_secs_f64() / total.as_secs_f64().max(f64::MIN_POSITIVE) * 100.0;
+        let input = match input.char_indices().nth(INPUT_WIDTH) {
+            Some((end, _)) => format!("{}…", &input[..end]),
+            None => input.to_string(),
+        };
+        [
+            Cell::from(line),
+            Cell::from(format!("{time:.1?}")),
+            Cell::from(format!("{share:.1}%")),
+            Cell::from(input),
+        ]
+        .map(Some)
+        .to_vec()
+    });
+    Table {
+        name: format!("slowest {count}"),
+        columns: ["line", "time", "share", "input"]
+            .map(String::from)
+            .to_vec(),
+        rows: rows.collect(),
+    }
+}
+
+/// Lines per bucket, with bucket edges at 1, 2 and 5 times every power of ten nanoseconds.
+fn histogram(times: &[Duration]) -> String {
+    let edges: Vec<u128> = (0..12)
+        .flat_map(|exp| [1, 2, 5].map(|step| step * 10_u128.pow(exp)))
+        .collect();
+    let bucket = |time: &Duration| {
+        edges
+            .iter()
+            .rposition(|&edge| edge <= time.as_nanos())
+            .unwrap_or(0)
+    };
+
+    let mut counts = vec![0; edges.len()];
+    for time in times {
+        counts[bucket(time)] += 1;
+    }
+    let first = counts.iter().position(|&count| count > 0).unwrap_or(0);
+    let last = counts.iter().rposition(|&count| count > 0).unwrap_or(0);
+    let max = counts.iter().copied().max().unwrap_or(1).max(1);
+
+    let label = |idx: usize| match edges.get(idx) {
+        Some(&edge) => format!("{:?}", Duration::from_nanos(edge as u64)),
+        None => "∞".to_string(),
+    };
+    (first..=last)
+        .map(|idx| {
+            let range = format!("{} – {}", label(idx), label(idx + 1));
+            let bar = "█".repeat((counts[idx] * BAR_WIDTH).div_ceil(max));
+            format!("  {range:>15}  {bar} {}\n", counts[idx])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_by_magnitude() {
+        let times = [900, 1_500, 1_900, 4_000, 30_000].map(Duration::from_nanos);
+        let lines: Vec<String> = histogram(&times).lines().map(String::from).collect();
+        assert_eq!(lines.len(), 6);
+        assert!(lines[0].trim_start().starts_with("500ns – 1µs  █"));
+        assert!(lines[1].ends_with("█ 2"));
+        assert!(lines[3].ends_with(" 0"));
+        assert!(lines[5].trim_start().starts_with("20µs – 50µs"));
+    }
+}
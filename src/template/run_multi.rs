@@ -4,10 +4,15 @@ use crate::template::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET, Day};
 
 use super::{
     all_days,
-    timings::{Timing, Timings},
+    timings::{Timing, Timings, VariantTiming},
 };
 
-pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -> Option<Timings> {
+pub fn run_multi(
+    days_to_run: &HashSet<Day>,
+    is_release: bool,
+    is_timed: bool,
+    is_generated: bool,
+) -> Option<Timings> {
     let mut timings: Vec<Timing> = Vec::with_capacity(days_to_run.len());
 
     let mut need_space = false;
@@ -24,7 +29,8 @@ pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -
             println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
             println!("------");
 
-            let output = child_commands::run_solution(day, is_timed, is_release).unwrap();
+            let output =
+                child_commands::run_solution(day, is_timed, is_release, is_generated).unwrap();
 
             if output.is_empty() {
                 println!("Not solved.");
@@ -69,6 +75,7 @@ pub fn get_path_for_bin(day: Day) -> String {
 pub mod child_commands {
     use super::{Error, get_path_for_bin};
     use crate::template::Day;
+    use crate::template::commands::solve::SolveOptions;
     use std::{
         io::{BufRead, BufReader},
         path::Path,
@@ -77,12 +84,22 @@ pub mod child_commands {
     };
 
     /// Run the solution bin for a given day
-    pub fn run_solution(day: Day, is_timed: bool, is_release: bool) -> Result<Vec<String>, Error> {
+    pub fn run_solution(
+        day: Day,
+        is_timed: bool,
+        is_release: bool,
+        is_generated: bool,
+    ) -> Result<Vec<String>, Error> {
         // skip command invocation for days that have not been scaffolded yet.
         if !Path::new(&get_path_for_bin(day)).exists() {
             return Ok(vec![]);
         }
 
+        // skip days without an input from `cargo gen`.
+        if is_generated && !Path::new(&format!("data/generated/{day}.txt")).exists() {
+            return Ok(vec![]);
+        }
+
         let day_padded = day.to_string();
         let mut args = vec!["run", "--quiet", "--bin", &day_padded];
 
@@ -90,11 +107,15 @@ pub mod child_commands {
             args.push("--release");
         }
 
-        if is_timed {
-            // mirror `--time` flag to child invocations.
-            args.push("--");
-            args.push("--time");
+        // mirror `--time` and `--generated` to child invocations.
+        let options = SolveOptions {
+            time: is_timed,
+            generated: is_generated,
+            ..SolveOptions::default()
         }
+        .to_args();
+        args.push("--");
+        args.extend(options.iter().map(String::as_str));
 
         // spawn child command with piped stdout/stderr.
         // forward output to stdout/stderr while grabbing stdout lines.
@@ -134,6 +155,7 @@ pub mod child_commands {
             part_1: None,
             part_2: None,
             total_nanos: 0_f64,
+            variants: vec![],
         };
 
         output
@@ -148,10 +170,23 @@ pub mod child_commands {
                     return None;
                 };
 
-                let part = l.split(':').next()?;
+                let part = l.split(": ").next()?;
                 Some((part, timing_str, nanos))
             })
             .for_each(|(part, timing_str, nanos)| {
+                // variants are labelled like `Part 1 [name]`.
+                if let Some((part, name)) = part.split_once(" [") {
+                    let number = part.rsplit(' ').next().and_then(|n| n.parse().ok());
+                    if let Some(part) = number {
+                        timings.variants.push(super::VariantTiming {
+                            part,
+                            name: name.trim_end_matches(']').into(),
+                            time: timing_str.into(),
+                        });
+                    }
+                    return;
+                }
+
                 if part.contains("Part 1") {
                     timings.part_1 = Some(timing_str.into());
                 } else if part.contains("Part 2") {
@@ -253,5 +288,23 @@ pub mod child_commands {
             assert_eq!(res.part_1.is_none(), true);
             assert_eq!(res.part_2.is_none(), true);
         }
+
+        #[test]
+        fn parses_variants() {
+            let res = parse_exec_time(
+                &[
+                    "Part 1: 10 (1ms @ 100 samples)".into(),
+                    "Part 1 [part_one_scan]: 10 (2ms @ 50 samples)".into(),
+                    "Part 2 [scan::part_two]: 10 (3ms @ 10 samples)".into(),
+                ],
+                day!(1),
+            );
+            assert_approx_eq!(res.total_nanos, 1000000_f64);
+            assert_eq!(res.variants.len(), 2);
+            assert_eq!(res.variants[0].name, "part_one_scan");
+            assert_eq!(res.variants[1].part, 2);
+            assert_eq!(res.variants[1].name, "scan::part_two");
+            assert_eq!(res.variants[1].time, "3ms");
+        }
     }
 }
@@ -1,4 +1,5 @@
 /// Encapsulates code that interacts with solution functions.
+use std::collections::HashMap;
 use std::fmt::Display;
 use std::hint::black_box;
 use std::io::{Write, stdout};
@@ -6,19 +7,76 @@ use std::process::Output;
 use std::time::{Duration, Instant};
 use std::{cmp, env, process};
 
+use tinyjson::JsonValue;
+
+use crate::explain;
+use crate::ocr::{self, OcrError};
 use crate::template::ANSI_BOLD;
+use crate::template::commands::solve::SolveOptions;
+use crate::template::variants::Variant;
 use crate::template::{ANSI_ITALIC, ANSI_RESET, Day, aoc_cli};
+use crate::verify;
 
-pub fn run_part<I: Copy, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
+/// Reads the flags the day's binary was started with, exiting if they can't be understood.
+pub fn parse_options() -> SolveOptions {
+    let args: Vec<String> = env::args().collect();
+    SolveOptions::from_args(&args).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(1);
+    })
+}
+
+pub fn run_part<I: Copy, T: Display>(
+    func: impl Fn(I) -> Option<T>,
+    input: I,
+    day: Day,
+    part: u8,
+    options: &SolveOptions,
+) {
     let part_str = format!("Part {part}");
+    let json = options.json;
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+    let (result, duration, samples) = run_timed(func, input, options, |result| {
+        if !json {
+            print_result(result, &part_str, "");
+        }
+    });
+    let tables = explain::take();
+    let verified = verify::take();
 
-    print_result(&result, &part_str, &format_duration(&duration, samples));
+    if json {
+        print_json(&result, day, part, &duration, &tables, verified);
+    } else {
+        print_result(&result, &part_str, &format_duration(&duration, samples));
+        if options.verify {
+            println!("{ANSI_ITALIC}{verified} witnesses verified{ANSI_RESET}");
+        }
+        if options.explain {
+            print_explanation(&tables);
+        }
+    }
 
     if let Some(result) = result {
-        submit_result(result, day, part);
+        submit_result(result, day, part, options);
+    }
+}
+
+/// Benchmarks the variants registered for a part, printed right below the part itself. Variants
+/// only run when timing, and not when printing JSON.
+pub fn run_variants(variants: &[Variant], input: &str, part: u8, options: &SolveOptions) {
+    if !options.time || options.json {
+        return;
+    }
+
+    for variant in variants {
+        let label = format!("Part {part} [{}]", variant.name);
+        let result = (variant.answer)(input);
+        print_result(&result, &label, "");
+
+        let timer = Instant::now();
+        (variant.run)(input);
+        let (duration, samples) = bench(variant.run, input, &timer.elapsed(), false);
+        print_result(&result, &label, &format_duration(&duration, samples));
     }
 }
 
@@ -28,6 +86,7 @@ pub fn run_part<I: Copy, T: Display>(func: impl Fn(I) -> Option<T>, input: I, da
 fn run_timed<I: Copy, T>(
     func: impl Fn(I) -> T,
     input: I,
+    options: &SolveOptions,
     hook: impl Fn(&T),
 ) -> (T, Duration, u128) {
     let timer = Instant::now();
@@ -41,8 +100,8 @@ fn run_timed<I: Copy, T>(
 
     hook(&result);
 
-    let run = if std::env::args().any(|x| x == "--time") {
-        bench(func, input, &base_time)
+    let run = if options.time {
+        bench(func, input, &base_time, options.json)
     } else {
         (base_time, 1)
     };
@@ -50,11 +109,20 @@ fn run_timed<I: Copy, T>(
     (result, run.0, run.1)
 }
 
-fn bench<I: Copy, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) -> (Duration, u128) {
+/// Runs `func` repeatedly and returns its average time. `quiet` keeps stdout parseable when
+/// results are printed as JSON.
+fn bench<I: Copy, T>(
+    func: impl Fn(I) -> T,
+    input: I,
+    base_time: &Duration,
+    quiet: bool,
+) -> (Duration, u128) {
     let mut stdout = stdout();
 
-    print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
-    let _ = stdout.flush();
+    if !quiet {
+        print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
+        let _ = stdout.flush();
+    }
 
     let bench_iterations =
         (Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10)).clamp(10, 10000);
@@ -95,15 +163,22 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
 
     match result {
         Some(result) => {
-            if result.to_string().contains('\n') {
-                let str = format!("{part}: ▼ {duration_str}");
+            let result = result.to_string();
+            if result.contains('\n') {
                 if is_intermediate_result {
-                    print!("{str}");
-                } else {
-                    print!("\r");
-                    println!("{str}");
-                    println!("{result}");
+                    print!("{part}: ▼");
+                    return;
                 }
+                print!("\r");
+                // pictures of letters are shown decoded, with the picture below for reference.
+                match ocr::recognize_str(&result) {
+                    Ok(letters) => {
+                        println!("{part}: {ANSI_BOLD}{letters}{ANSI_RESET}{duration_str} ▼");
+                    }
+                    Err(OcrError::NotAPicture) => println!("{part}: ▼ {duration_str}"),
+                    Err(err) => println!("{part}: ▼ {duration_str}\ncould not read letters: {err}"),
+                }
+                println!("{result}");
             } else {
                 let str = format!("{part}: {ANSI_BOLD}{result}{ANSI_RESET}{duration_str}");
                 if is_intermediate_result {
@@ -125,36 +200,73 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
     }
 }
 
-/// Parse the arguments passed to `solve` and try to submit one part of the solution if:
+fn print_explanation(tables: &[explain::Table]) {
+    if tables.is_empty() {
+        println!("{ANSI_ITALIC}nothing to explain{ANSI_RESET}");
+    } else {
+        print!("{}", explain::render(tables));
+    }
+}
+
+/// Prints a part as one line of JSON, with its answer as a string.
+fn print_json<T: Display>(
+    result: &Option<T>,
+    day: Day,
+    part: u8,
+    duration: &Duration,
+    tables: &[explain::Table],
+    verified: usize,
+) {
+    let answer = match result {
+        Some(result) => JsonValue::String(answer(result).unwrap_or_else(|_| result.to_string())),
+        None => JsonValue::Null,
+    };
+    let fields = [
+        ("day", JsonValue::String(day.to_string())),
+        ("part", JsonValue::Number(part.into())),
+        ("answer", answer),
+        ("nanos", JsonValue::Number(duration.as_nanos() as f64)),
+        ("explain", explain::to_json(tables)),
+        ("verified", JsonValue::Number(verified as f64)),
+    ];
+    let object = fields.map(|(key, value)| (key.to_string(), value));
+    match JsonValue::Object(HashMap::from(object)).stringify() {
+        Ok(json) => println!("{json}"),
+        Err(err) => eprintln!("could not write part {part} as JSON: {err}"),
+    }
+}
+
+/// The answer to submit for a result. Multi-line results that are pictures of letters are
+/// submitted as the letters, other multi-line results as they are.
+fn answer<T: Display>(result: &T) -> Result<String, OcrError> {
+    let result = result.to_string();
+    if !result.contains('\n') {
+        return Ok(result);
+    }
+    match ocr::recognize_str(&result) {
+        Err(OcrError::NotAPicture) => Ok(result),
+        letters => letters,
+    }
+}
+
+/// Try to submit one part of the solution if it was requested with `--submit` and:
 ///  1. we are in `--release` mode.
 ///  2. aoc-cli is installed.
 fn submit_result<T: Display>(
     result: T,
     day: Day,
     part: u8,
+    options: &SolveOptions,
 ) -> Option<Result<Output, aoc_cli::AocCommandError>> {
-    let args: Vec<String> = env::args().collect();
-
-    if !args.contains(&"--submit".into()) {
+    if options.submit != Some(part) {
         return None;
     }
 
-    if args.len() < 3 {
-        eprintln!("Unexpected command-line input. Format: cargo solve 1 --submit 1");
+    if options.generated {
+        eprintln!("Not submitting part {part}, the answer is for a generated input.");
         process::exit(1);
     }
 
-    let part_index = args.iter().position(|x| x == "--submit").unwrap() + 1;
-
-    let Ok(part_submit) = args[part_index].parse::<u8>() else {
-        eprintln!("Unexpected command-line input. Format: cargo solve 1 --submit 1");
-        process::exit(1);
-    };
-
-    if part_submit != part {
-        return None;
-    }
-
     if aoc_cli::check().is_err() {
         eprintln!(
             "command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it."
@@ -162,6 +274,11 @@ fn submit_result<T: Display>(
         process::exit(1);
     }
 
+    let answer = answer(&result).unwrap_or_else(|err| {
+        eprintln!("Not submitting part {part}, could not read letters: {err}");
+        process::exit(1);
+    });
+
     println!("Submitting result via aoc-cli...");
-    Some(aoc_cli::submit(day, part, &result.to_string()))
+    Some(aoc_cli::submit(day, part, &answer))
 }
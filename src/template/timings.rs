@@ -12,6 +12,16 @@ pub struct Timing {
     pub part_1: Option<String>,
     pub part_2: Option<String>,
     pub total_nanos: f64,
+    /// Alternative implementations registered with `variants!`. Not part of the total.
+    pub variants: Vec<VariantTiming>,
+}
+
+/// Benchmark time of a variant of a part.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VariantTiming {
+    pub part: u8,
+    pub name: String,
+    pub time: String,
 }
 
 /// Represents benchmark times for a set of days.
@@ -51,7 +61,7 @@ impl Timings {
             }
         }
 
-        data.sort_unstable_by(|a, b| a.day.cmp(&b.day));
+        data.sort_unstable_by_key(|t| t.day);
         Timings { data }
     }
 
@@ -133,6 +143,15 @@ impl From<&Timing> for JsonValue {
             },
         );
 
+        let variants = value.variants.iter().map(|variant| {
+            JsonValue::Object(HashMap::from([
+                ("part".into(), JsonValue::Number(variant.part.into())),
+                ("name".into(), JsonValue::String(variant.name.clone())),
+                ("time".into(), JsonValue::String(variant.time.clone())),
+            ]))
+        });
+        map.insert("variants".into(), JsonValue::Array(variants.collect()));
+
         JsonValue::Object(map)
     }
 }
@@ -166,11 +185,51 @@ impl TryFrom<&JsonValue> for Timing {
             .and_then(|v| v.get::<f64>().copied())
             .ok_or("Expected timing.total_nanos to be a number.")?;
 
+        // timings stored before variants existed don't have the key.
+        let variants = match json.get("variants") {
+            None => vec![],
+            Some(variants) => variants
+                .get::<Vec<JsonValue>>()
+                .ok_or("Expected timing.variants to be an array.")?
+                .iter()
+                .map(VariantTiming::try_from)
+                .collect::<Result<_, _>>()?,
+        };
+
         Ok(Timing {
             day,
             part_1: part_1.cloned(),
             part_2: part_2.cloned(),
             total_nanos,
+            variants,
+        })
+    }
+}
+
+impl TryFrom<&JsonValue> for VariantTiming {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected variant timing to be a JSON object.")?;
+        let text = |key: &str| {
+            json.get(key)
+                .and_then(|v| v.get::<String>())
+                .cloned()
+                .ok_or(format!("Expected variant.{key} to be a string."))
+        };
+
+        let part = json
+            .get("part")
+            .and_then(|v| v.get::<f64>())
+            .map(|&part| part as u8)
+            .ok_or("Expected variant.part to be a number.")?;
+
+        Ok(VariantTiming {
+            part,
+            name: text("name")?,
+            time: text("time")?,
         })
     }
 }
@@ -191,18 +250,21 @@ mod tests {
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
                     total_nanos: 3e+10,
+                    variants: vec![],
                 },
                 Timing {
                     day: day!(2),
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
                     total_nanos: 7e+10,
+                    variants: vec![],
                 },
                 Timing {
                     day: day!(4),
                     part_1: Some("40ms".into()),
                     part_2: None,
                     total_nanos: 4e+10,
+                    variants: vec![],
                 },
             ],
         }
@@ -282,6 +344,7 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: Some("2ms".into()),
                     total_nanos: 3_000_000_000_f64,
+                    variants: vec![],
                 }],
             };
 
@@ -296,6 +359,7 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: None,
                     total_nanos: 1_000_000_000_f64,
+                    variants: vec![],
                 }],
             };
 
@@ -310,6 +374,7 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0.0,
+                    variants: vec![],
                 }],
             };
 
@@ -334,6 +399,7 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    variants: vec![],
                 }],
             };
             let merged = timings.merge(&other);
@@ -354,6 +420,7 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    variants: vec![],
                 }],
             };
             let merged = timings.merge(&other);
@@ -0,0 +1,134 @@
+/// Alternative implementations of a part, registered with [`variants!`](crate::variants).
+use std::path::Path;
+
+use crate::generate;
+use crate::template::{Day, read_file, read_file_part};
+
+/// Generated inputs every variant is checked on, in addition to the examples.
+const GENERATED_SEEDS: u64 = 3;
+/// Size of those inputs. Small enough that slow reference variants stay quick in debug builds.
+const GENERATED_SIZE: f64 = 0.1;
+
+pub struct Variant {
+    pub name: &'static str,
+    /// Solves the part and returns the answer as text, so that variants can be compared.
+    pub answer: fn(&str) -> Option<String>,
+    /// Solves the part and discards the answer, for benchmarking.
+    pub run: fn(&str),
+}
+
+/// Defaults for days without variants. `variants!` defines functions with the same names in the
+/// day's binary, which take precedence over these glob imports.
+pub mod none {
+    use super::Variant;
+
+    pub fn part_one_variants() -> Vec<Variant> {
+        vec![]
+    }
+
+    pub fn part_two_variants() -> Vec<Variant> {
+        vec![]
+    }
+}
+
+/// Registers alternative implementations of a day's parts, e.g. a slow but obvious version to
+/// check a fast one against:
+///
+/// ```ignore
+/// advent_of_code::variants! {
+///     part_one: [part_one_scan],
+///     part_two: [part_two_scan],
+/// }
+/// ```
+///
+/// Every variant is checked against the part on the example and on generated inputs by
+/// `cargo test`, and benchmarked next to the part by `cargo time`. Variants can be gated with
+/// `#[cfg(...)]`.
+#[macro_export]
+macro_rules! variants {
+    ($($part:ident: [$($(#[$meta:meta])* $variant:path),* $(,)?]),* $(,)?) => {
+        $( $crate::variants!(@part $part, [$($(#[$meta])* $variant),*]); )*
+    };
+
+    (@part part_one, [$($(#[$meta:meta])* $variant:path),*]) => {
+        $crate::variants!(@impl part_one, part_one_variants, 1, [$($(#[$meta])* $variant),*]);
+    };
+    (@part part_two, [$($(#[$meta:meta])* $variant:path),*]) => {
+        $crate::variants!(@impl part_two, part_two_variants, 2, [$($(#[$meta])* $variant),*]);
+    };
+
+    (@impl $func:ident, $variants:ident, $part:expr, [$($(#[$meta:meta])* $variant:path),*]) => {
+        fn $variants() -> Vec<$crate::template::variants::Variant> {
+            #[allow(unused_mut)]
+            let mut variants = vec![];
+            $(
+                $(#[$meta])*
+                variants.push($crate::variant!($variant));
+            )*
+            variants
+        }
+
+        #[cfg(test)]
+        mod $variants {
+            #[test]
+            fn agree_with_the_part() {
+                $crate::template::variants::cross_check(
+                    super::DAY,
+                    $part,
+                    &$crate::variant!(stringify!($func), super::$func),
+                    &super::$variants(),
+                );
+            }
+        }
+    };
+}
+
+/// A part function as a [`Variant`].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! variant {
+    ($func:path) => {
+        $crate::variant!(stringify!($func), $func)
+    };
+    ($name:expr, $func:path) => {
+        $crate::template::variants::Variant {
+            name: $name,
+            answer: |input| $func(input).map(|answer| answer.to_string()),
+            run: |input| {
+                std::hint::black_box($func(std::hint::black_box(input)));
+            },
+        }
+    };
+}
+
+/// Panics unless every variant gives the same answer as `reference` on the example for `part` and
+/// on inputs from the day's generator.
+pub fn cross_check(day: Day, part: u8, reference: &Variant, variants: &[Variant]) {
+    if variants.is_empty() {
+        return;
+    }
+    let example = if Path::new(&format!("data/examples/{day}.txt")).exists() {
+        read_file("examples", day)
+    } else {
+        read_file_part("examples", day, part)
+    };
+    let generated = (0..GENERATED_SEEDS).filter_map(|seed| {
+        let input = generate::input(day, GENERATED_SIZE, seed)?;
+        Some((format!("generated input (seed {seed})"), input))
+    });
+
+    for (name, input) in [("example".to_string(), example)]
+        .into_iter()
+        .chain(generated)
+    {
+        let expected = (reference.answer)(&input);
+        for variant in variants {
+            let answer = (variant.answer)(&input);
+            assert_eq!(
+                answer, expected,
+                "{} disagrees with {} on the {name}",
+                variant.name, reference.name
+            );
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! Independent checks of the witnesses behind answers.
+//!
+//! Optimizing solvers only report a number, and a bug in the search can produce a plausible one.
+//! Parts that know how the number was reached, e.g. which buttons were pressed on a machine, pass
+//! that witness to [`witness`] with a check that recomputes its effect straight from the input.
+//! Checks run in debug builds, which includes tests, and in release builds started with
+//! `--verify`, as done by `cargo verify`. The runner settles which with [`enable`]. A failed check
+//! panics, so it can't go unnoticed.
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(cfg!(debug_assertions));
+static VERIFIED: AtomicUsize = AtomicUsize::new(0);
+
+/// Starts or stops checking witnesses.
+pub fn enable(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether witnesses are being checked.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Runs `check` on the witness for `what`, panicking with the error it reports.
+pub fn witness(what: impl Display, check: impl FnOnce() -> Result<(), String>) {
+    if !enabled() {
+        return;
+    }
+    if let Err(err) = check() {
+        panic!("witness check failed for {what}: {err}");
+    }
+    VERIFIED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Resets and returns the number of witnesses that passed their check so far.
+pub fn take() -> usize {
+    VERIFIED.swap(0, Ordering::Relaxed)
+}
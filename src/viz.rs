@@ -0,0 +1,506 @@
+//! Terminal playback and recording of simulations.
+//!
+//! Solutions emit frames through [`frame`], which does nothing unless the binary was started with
+//! `--visualize` (e.g. `cargo solve 4 --visualize`) or `--record <file>`. Frames are closures so
+//! that rendering costs nothing in normal runs and benchmarks; work that only feeds frames can be
+//! skipped by checking [`enabled`]. Recordings are written to `.assets/` by [`finish`], see
+//! [`crate::record`] for the formats.
+//!
+//! Playback is controlled by typing a command and pressing enter, so it works in any terminal:
+//! an empty line pauses or resumes, `n` shows the next frame and pauses, `+` and `-` double or
+//! halve the frame rate and `q` skips the remaining frames.
+use std::env;
+use std::fmt::{Display, Write as _};
+use std::fs;
+use std::io::{BufRead, Write, stdin, stdout};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, channel};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::grid::Grid;
+use crate::record::{self, Format, Picture, Scene};
+use crate::template::{ANSI_BOLD, ANSI_RESET};
+
+pub const DEFAULT_FPS: u32 = 10;
+const MAX_FPS: u32 = 240;
+
+const ANSI_CLEAR: &str = "\x1b[H\x1b[2J";
+const ASSETS_DIR: &str = ".assets";
+/// Width in characters of scenes drawn in the terminal.
+const SCENE_COLUMNS: usize = 80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Grey,
+}
+
+impl Colour {
+    fn code(self) -> &'static str {
+        match self {
+            Colour::Red => "\x1b[31m",
+            Colour::Green => "\x1b[32m",
+            Colour::Yellow => "\x1b[33m",
+            Colour::Blue => "\x1b[34m",
+            Colour::Magenta => "\x1b[35m",
+            Colour::Cyan => "\x1b[36m",
+            Colour::White => "\x1b[97m",
+            Colour::Grey => "\x1b[90m",
+        }
+    }
+}
+
+/// `text` wrapped in the escape codes for `colour`.
+pub fn paint(text: impl Display, colour: Colour) -> String {
+    format!("{}{text}{ANSI_RESET}", colour.code())
+}
+
+/// Renders a grid one character per cell. Escape codes are only emitted where the colour changes,
+/// which keeps large frames cheap to print.
+pub fn render_grid<T>(
+    grid: &Grid<T>,
+    mut cell: impl FnMut((usize, usize), &T) -> (char, Option<Colour>),
+) -> String {
+    let mut out = String::with_capacity((grid.width() + 1) * grid.height());
+    for (y, row) in grid.rows().enumerate() {
+        let mut current = None;
+        for (x, value) in row.iter().enumerate() {
+            let (ch, colour) = cell((x, y), value);
+            if colour != current {
+                out.push_str(colour.map_or(ANSI_RESET, Colour::code));
+                current = colour;
+            }
+            out.push(ch);
+        }
+        if current.is_some() {
+            out.push_str(ANSI_RESET);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A frame as text for the terminal, and optionally as a picture for recordings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    text: String,
+    picture: Option<Picture>,
+}
+
+impl Frame {
+    /// A frame that only shows up in the terminal.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            picture: None,
+        }
+    }
+
+    /// A grid drawn one character per cell, and recorded as one coloured square per cell. Cells without
+    /// a colour are recorded as background.
+    pub fn grid<T>(
+        grid: &Grid<T>,
+        mut cell: impl FnMut((usize, usize), &T) -> (char, Option<Colour>),
+    ) -> Self {
+        let cells = Grid::from_fn(grid.width(), grid.height(), |pos| cell(pos, &grid[pos]));
+        Self {
+            text: render_grid(&cells, |_, &cell| cell),
+            picture: Some(Picture::Raster(cells.map(|&(_, colour)| colour))),
+        }
+    }
+
+    /// Vector shapes, drawn in the terminal with block characters.
+    pub fn scene(scene: Scene) -> Self {
+        let (width, height) = scene.size();
+        // terminal characters are about twice as tall as they are wide.
+        let rows = (SCENE_COLUMNS as f64 * height / width / 2.0).round() as usize;
+        let raster = scene.rasterize(SCENE_COLUMNS, rows.max(1), 0.5, 1.0);
+        Self {
+            text: render_grid(&raster, |_, &colour| {
+                (if colour.is_some() { '█' } else { ' ' }, colour)
+            }),
+            picture: Some(Picture::Scene(scene)),
+        }
+    }
+
+    /// Sets what gets recorded, keeping the text.
+    pub fn with_picture(mut self, picture: Picture) -> Self {
+        self.picture = Some(picture);
+        self
+    }
+
+    /// Adds a line of text below the frame in the terminal.
+    pub fn caption(mut self, caption: impl Display) -> Self {
+        let _ = write!(self.text, "\n{caption}");
+        self
+    }
+}
+
+impl From<String> for Frame {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// How frames are played back and recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub fps: u32,
+    /// Start paused, so that every frame has to be stepped through.
+    pub paused: bool,
+    /// Play frames in the terminal.
+    pub show: bool,
+    /// Write frames to this file. Relative paths are inside `.assets/`.
+    pub record: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            fps: DEFAULT_FPS,
+            paused: false,
+            show: true,
+            record: None,
+        }
+    }
+}
+
+impl Options {
+    /// Reads `--visualize`, `--record <file>`, `--fps <n>` and `--step` from a day's command line.
+    /// Returns `None` if neither playback nor recording was requested, or if the solution is being
+    /// benchmarked with `--time`.
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        let value = |name: &str| {
+            let idx = args.iter().position(|arg| arg == name)?;
+            args.get(idx + 1)
+        };
+        let show = args.iter().any(|arg| arg == "--visualize");
+        let record = value("--record").map(PathBuf::from);
+        if !show && record.is_none() || args.iter().any(|arg| arg == "--time") {
+            return None;
+        }
+
+        let fps = value("--fps")
+            .and_then(|fps| fps.parse().ok())
+            .unwrap_or(DEFAULT_FPS);
+        Some(Self {
+            fps: fps.clamp(1, MAX_FPS),
+            paused: args.iter().any(|arg| arg == "--step"),
+            show,
+            record,
+        })
+    }
+
+    /// The arguments that make a day's binary play or record frames with these options.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = vec![];
+        if self.show {
+            args.push("--visualize".into());
+        }
+        if let Some(path) = &self.record {
+            args.extend(["--record".into(), path.display().to_string()]);
+        }
+        args.extend(["--fps".into(), self.fps.to_string()]);
+        if self.paused {
+            args.push("--step".into());
+        }
+        args
+    }
+}
+
+/// Whether frames are being shown or recorded.
+pub fn enabled() -> bool {
+    session().is_some()
+}
+
+/// Records and shows the frame produced by `render`, then waits until the next one is due.
+pub fn frame<F: Into<Frame>>(render: impl FnOnce() -> F) {
+    let Some(session) = session() else {
+        return;
+    };
+    let mut session = session.lock().unwrap_or_else(|err| err.into_inner());
+    let Session { player, recorder } = &mut *session;
+
+    let player = player.as_mut().filter(|player| !player.skipped);
+    if player.is_none() && recorder.is_none() {
+        return;
+    }
+
+    let Frame { text, picture } = render().into();
+    if let (Some(recorder), Some(picture)) = (recorder, picture) {
+        recorder.pictures.push(picture);
+    }
+    if let Some(player) = player {
+        player.show(text);
+    }
+}
+
+/// Writes the recording, if there is one. Called by [`crate::solution`] once all parts have run.
+pub fn finish() {
+    let Some(session) = session() else {
+        return;
+    };
+    let mut session = session.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some(recorder) = session.recorder.take() {
+        recorder.save();
+    }
+}
+
+fn session() -> Option<&'static Mutex<Session>> {
+    static SESSION: OnceLock<Option<Mutex<Session>>> = OnceLock::new();
+    SESSION
+        .get_or_init(|| {
+            let args: Vec<String> = env::args().collect();
+            let options = Options::from_args(&args)?;
+            Some(Mutex::new(Session {
+                recorder: options
+                    .record
+                    .as_deref()
+                    .map(|path| Recorder::new(path, options.fps)),
+                player: options.show.then(|| Player::new(options)),
+            }))
+        })
+        .as_ref()
+}
+
+struct Session {
+    player: Option<Player>,
+    recorder: Option<Recorder>,
+}
+
+struct Recorder {
+    path: PathBuf,
+    format: Format,
+    fps: u32,
+    pictures: Vec<Picture>,
+}
+
+impl Recorder {
+    fn new(path: &Path, fps: u32) -> Self {
+        let format = Format::from_path(path).unwrap_or_else(|| {
+            panic!("can't record to {path:?}: expected a .gif, .png, .apng or .svg file")
+        });
+        Self {
+            path: Path::new(ASSETS_DIR).join(path),
+            format,
+            fps,
+            pictures: vec![],
+        }
+    }
+
+    fn save(self) {
+        if self.pictures.is_empty() {
+            eprintln!("Nothing to record: this day doesn't draw any pictures.");
+            return;
+        }
+
+        let bytes = record::encode(self.format, &self.pictures, self.fps);
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).unwrap_or_else(|err| panic!("could not create {dir:?}: {err}"));
+        }
+        fs::write(&self.path, bytes)
+            .unwrap_or_else(|err| panic!("could not write {:?}: {err}", self.path));
+
+        let frames = match self.format {
+            Format::Svg => 1,
+            Format::Gif | Format::Apng => self.pictures.len(),
+        };
+        println!("Recorded {frames} frame(s) to {}.", self.path.display());
+    }
+}
+
+enum Command {
+    TogglePause,
+    Step,
+    Faster,
+    Slower,
+    Skip,
+}
+
+struct Player {
+    options: Options,
+    frame_count: usize,
+    last_frame: String,
+    skipped: bool,
+    /// `None` once stdin is closed, after which playback can't be controlled anymore.
+    commands: Option<Receiver<Command>>,
+}
+
+impl Player {
+    fn new(options: Options) -> Self {
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            for line in stdin().lock().lines() {
+                let Ok(line) = line else { break };
+                let command = match line.trim() {
+                    "" | "p" => Command::TogglePause,
+                    "n" => Command::Step,
+                    "+" => Command::Faster,
+                    "-" => Command::Slower,
+                    "q" => Command::Skip,
+                    _ => continue,
+                };
+                if sender.send(command).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            options,
+            frame_count: 0,
+            last_frame: String::new(),
+            skipped: false,
+            commands: Some(receiver),
+        }
+    }
+
+    fn show(&mut self, frame: String) {
+        self.frame_count += 1;
+        self.last_frame = frame;
+        self.draw();
+        self.wait();
+    }
+
+    fn draw(&self) {
+        let Options { fps, paused, .. } = self.options;
+        let mut status = format!(
+            "{ANSI_BOLD}frame {}{ANSI_RESET} · {fps} fps",
+            self.frame_count
+        );
+        if paused {
+            let _ = write!(status, " · paused");
+        }
+        let _ = write!(
+            status,
+            " · {}",
+            paint("⏎ pause/resume, n step, +/- speed, q skip", Colour::Grey)
+        );
+
+        let mut stdout = stdout().lock();
+        let _ = write!(
+            stdout,
+            "{ANSI_CLEAR}{}\n{status}\n",
+            self.last_frame.trim_end()
+        );
+        let _ = stdout.flush();
+    }
+
+    /// Blocks until the next frame is due, handling commands in the meantime.
+    fn wait(&mut self) {
+        let started = Instant::now();
+        loop {
+            let Some(commands) = &self.commands else {
+                thread::sleep(self.frame_time().saturating_sub(started.elapsed()));
+                return;
+            };
+
+            let command = if self.options.paused {
+                commands.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                commands.recv_timeout(self.frame_time().saturating_sub(started.elapsed()))
+            };
+
+            match command {
+                Ok(Command::TogglePause) => {
+                    self.options.paused = !self.options.paused;
+                    if !self.options.paused {
+                        return;
+                    }
+                }
+                Ok(Command::Step) => {
+                    self.options.paused = true;
+                    return;
+                }
+                Ok(Command::Faster) => self.options.fps = (self.options.fps * 2).min(MAX_FPS),
+                Ok(Command::Slower) => self.options.fps = (self.options.fps / 2).max(1),
+                Ok(Command::Skip) => {
+                    self.skipped = true;
+                    return;
+                }
+                Err(RecvTimeoutError::Timeout) => return,
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.commands = None;
+                    self.options.paused = false;
+                }
+            }
+            self.draw();
+        }
+    }
+
+    fn frame_time(&self) -> Duration {
+        Duration::from_secs(1) / self.options.fps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn reads_options_from_args() {
+        assert_eq!(Options::from_args(&args(&["01"])), None);
+        assert_eq!(
+            Options::from_args(&args(&["01", "--visualize"])),
+            Some(Options::default())
+        );
+        assert_eq!(
+            Options::from_args(&args(&["01", "--visualize", "--fps", "30", "--step"])),
+            Some(Options {
+                fps: 30,
+                paused: true,
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            Options::from_args(&args(&["01", "--record", "out.gif"])),
+            Some(Options {
+                show: false,
+                record: Some("out.gif".into()),
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            Options::from_args(&args(&["01", "--visualize", "--time"])),
+            None
+        );
+
+        let options = Options {
+            fps: 5,
+            paused: true,
+            show: true,
+            record: Some("day 01.svg".into()),
+        };
+        let mut round_trip = args(&["01"]);
+        round_trip.extend(options.to_args());
+        assert_eq!(Options::from_args(&round_trip), Some(options));
+    }
+
+    #[test]
+    fn renders_colour_runs() {
+        let grid = Grid::parse("ab.\n..b\n", |b| b).unwrap();
+        let frame = render_grid(&grid, |_, &b| match b {
+            b'.' => ('.', None),
+            _ => ('#', Some(Colour::Red)),
+        });
+        let red = Colour::Red.code();
+        assert_eq!(
+            frame,
+            format!("{red}##{ANSI_RESET}.\n..{red}#{ANSI_RESET}\n")
+        );
+    }
+}